@@ -1,2 +1,3 @@
-pub mod bvh;
 pub mod aabb;
+#[allow(clippy::module_inception)]
+pub mod bvh;
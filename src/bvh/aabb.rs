@@ -90,7 +90,7 @@ impl Aabb {
         Aabb::new_from_interval(x, y, z)
     }
 
-    pub fn rotate(&self, x_rotation: f64, y_rotation: f64, z_rotation: f64) -> Aabb {
+    pub fn rotate(&self, _x_rotation: f64, y_rotation: f64, _z_rotation: f64) -> Aabb {
         let radians = degrees_to_radians(y_rotation);
         let sin_theta = radians.sin();
         let cos_theta = radians.cos();
@@ -203,3 +203,186 @@ impl Display for Aabb {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f64 = 1e-9;
+    const UNIVERSAL: Interval = Interval::get_universal_interval();
+
+    fn unit_box() -> Aabb {
+        Aabb::new_from_extrema_points(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0))
+    }
+
+    fn ray(origin: (f64, f64, f64), direction: (f64, f64, f64)) -> Ray {
+        Ray::new(
+            Point::new(origin.0, origin.1, origin.2),
+            Vector::new(direction.0, direction.1, direction.2),
+            None,
+        )
+    }
+
+    fn assert_hit(hit: Option<Interval>, min: f64, max: f64) {
+        let (t_min, t_max) = hit.expect("expected the box to be hit").get_min_max();
+
+        assert!(
+            (t_min - min).abs() < EPSILON && (t_max - max).abs() < EPSILON,
+            "expected a hit from {} to {}, got {} to {}",
+            min,
+            max,
+            t_min,
+            t_max
+        );
+    }
+
+    #[test]
+    fn extrema_points_can_come_in_any_order() {
+        let flipped =
+            Aabb::new_from_extrema_points(Point::new(1.0, -1.0, 1.0), Point::new(-1.0, 1.0, -1.0));
+
+        for axis in 0..3 {
+            assert_eq!(flipped.get_axis_interval(axis).get_min_max(), (-1.0, 1.0));
+        }
+    }
+
+    #[test]
+    fn flat_boxes_are_padded() {
+        let flat =
+            Aabb::new_from_extrema_points(Point::new(0.0, 2.0, 0.0), Point::new(1.0, 2.0, 1.0));
+
+        assert!(flat.get_axis_interval(1).get_size() >= 0.001);
+        assert!(
+            flat.hit(&ray((0.5, 5.0, 0.5), (0.0, -1.0, 0.0)), &UNIVERSAL)
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn ray_through_the_centre_enters_and_leaves() {
+        assert_hit(
+            unit_box().hit(&ray((-5.0, 0.0, 0.0), (1.0, 0.0, 0.0)), &UNIVERSAL),
+            4.0,
+            6.0,
+        );
+        assert_hit(
+            unit_box().hit(&ray((0.0, 0.0, 5.0), (0.0, 0.0, -2.0)), &UNIVERSAL),
+            2.0,
+            3.0,
+        );
+    }
+
+    #[test]
+    fn diagonal_ray_hits_through_the_corners() {
+        assert_hit(
+            unit_box().hit(&ray((-2.0, -2.0, -2.0), (1.0, 1.0, 1.0)), &UNIVERSAL),
+            1.0,
+            3.0,
+        );
+    }
+
+    #[test]
+    fn ray_missing_the_box() {
+        let aabb = unit_box();
+
+        assert!(
+            aabb.hit(&ray((-5.0, 2.0, 0.0), (1.0, 0.0, 0.0)), &UNIVERSAL)
+                .is_none()
+        );
+        assert!(
+            aabb.hit(&ray((-5.0, 0.0, 0.0), (1.0, 1.0, 0.0)), &UNIVERSAL)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn ray_pointing_away_misses() {
+        let away = ray((-5.0, 0.0, 0.0), (-1.0, 0.0, 0.0));
+
+        assert!(unit_box().hit(&away, &Interval::new(0.0, F_INF)).is_none());
+    }
+
+    #[test]
+    fn ray_starting_inside_hits_from_its_origin() {
+        assert_hit(
+            unit_box().hit(
+                &ray((0.0, 0.0, 0.0), (0.0, 1.0, 0.0)),
+                &Interval::new(0.0, F_INF),
+            ),
+            0.0,
+            1.0,
+        );
+    }
+
+    #[test]
+    fn hits_are_limited_to_ray_t() {
+        let through = ray((-5.0, 0.0, 0.0), (1.0, 0.0, 0.0));
+
+        assert!(unit_box().hit(&through, &Interval::new(0.0, 3.0)).is_none());
+        assert!(
+            unit_box()
+                .hit(&through, &Interval::new(7.0, F_INF))
+                .is_none()
+        );
+        assert_hit(unit_box().hit(&through, &Interval::new(5.0, 5.5)), 5.0, 5.5);
+    }
+
+    #[test]
+    fn axis_parallel_rays_outside_a_slab_miss() {
+        // NOTE: Zero direction components give infinite slab distances, which must
+        // still rule the box out when the origin is outside that slab
+        assert!(
+            unit_box()
+                .hit(&ray((0.0, 3.0, -5.0), (0.0, 0.0, 1.0)), &UNIVERSAL)
+                .is_none()
+        );
+        assert!(
+            unit_box()
+                .hit(&ray((3.0, 3.0, -5.0), (0.0, 0.0, 1.0)), &UNIVERSAL)
+                .is_none()
+        );
+        assert_hit(
+            unit_box().hit(&ray((0.5, -0.5, -5.0), (0.0, 0.0, 1.0)), &UNIVERSAL),
+            4.0,
+            6.0,
+        );
+    }
+
+    #[test]
+    fn grazing_ray_along_a_face_misses() {
+        // NOTE: 0 * inf is NaN on the face itself, which has to count as a miss rather
+        // than poisoning the interval
+        assert!(
+            unit_box()
+                .hit(&ray((-5.0, 1.0, 0.0), (1.0, 0.0, 0.0)), &UNIVERSAL)
+                .is_none()
+        );
+        assert!(
+            unit_box()
+                .hit(&ray((-5.0, -1.0, 0.0), (1.0, 0.0, 0.0)), &UNIVERSAL)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn merged_box_covers_both() {
+        let a = Aabb::new_from_extrema_points(Point::new(0.0, 0.0, 0.0), Point::new(1.0, 1.0, 1.0));
+        let b =
+            Aabb::new_from_extrema_points(Point::new(2.0, -1.0, 0.5), Point::new(3.0, 0.5, 4.0));
+        let merged = merge_aabb(&a, &b);
+
+        assert_eq!(merged.get_axis_interval(0).get_min_max(), (0.0, 3.0));
+        assert_eq!(merged.get_axis_interval(1).get_min_max(), (-1.0, 1.0));
+        assert_eq!(merged.get_axis_interval(2).get_min_max(), (0.0, 4.0));
+        assert_eq!(merged.get_longest_axis(), 2);
+    }
+
+    #[test]
+    fn translate_moves_every_axis() {
+        let moved = unit_box().translate(Vector::new(1.0, -2.0, 3.0));
+
+        assert_eq!(moved.get_axis_interval(0).get_min_max(), (0.0, 2.0));
+        assert_eq!(moved.get_axis_interval(1).get_min_max(), (-3.0, -1.0));
+        assert_eq!(moved.get_axis_interval(2).get_min_max(), (2.0, 4.0));
+    }
+}
@@ -3,9 +3,10 @@ use std::sync::Arc;
 
 use crate::{
     bvh::aabb::{Aabb, merge_aabb},
-    objects::hittable::{self, HitRecord, Hittable, HittableList},
+    objects::hittable::{HitRecord, Hittable, HittableList},
     ray::Ray,
-    utils::interval::{EMPTY_INTERVAL, Interval},
+    scene_file::writer::{Placement, SceneWriter},
+    utils::interval::Interval,
 };
 
 pub struct BvhNode {
@@ -59,8 +60,8 @@ impl BvhNode {
             let mid = (start + end) / 2;
             let mut bounding_box = Aabb::default();
 
-            for i in start..end {
-                bounding_box = merge_aabb(&bounding_box, &hittable_list[i].get_aabb());
+            for hittable in &hittable_list[start..end] {
+                bounding_box = merge_aabb(&bounding_box, &hittable.get_aabb());
             }
             let axis = bounding_box.get_longest_axis();
 
@@ -94,10 +95,8 @@ impl BvhNode {
 
 impl Hittable for BvhNode {
     fn hit(&self, ray: &Ray, interval: &Interval) -> Option<HitRecord> {
-        let bb_hit_interval: Option<Interval> = self.bounding_box.hit(ray, interval);
-        if let None = bb_hit_interval {
-            return None;
-        }
+        // NOTE: Missed this bb entirely, so nothing inside can be hit either
+        self.bounding_box.hit(ray, interval)?;
 
         // NOTE: Hit something in this bb, must be either left or right or both
         match (&self.left_child, &self.right_child) {
@@ -137,6 +136,27 @@ impl Hittable for BvhNode {
     fn clone_box(&self) -> Arc<dyn Hittable> {
         Arc::new(self.clone())
     }
+
+    // NOTE: The tree itself isn't worth keeping, only the objects at its leaves
+    fn write_scene(&self, writer: &mut SceneWriter, placement: &Placement) {
+        let child_placement = placement.for_children();
+
+        for child in [&self.left_child, &self.right_child, &self.hittable]
+            .into_iter()
+            .flatten()
+        {
+            child.write_scene(writer, &child_placement);
+        }
+    }
+
+    fn collect_lights(&self, lights: &mut HittableList) {
+        for child in [&self.left_child, &self.right_child, &self.hittable]
+            .into_iter()
+            .flatten()
+        {
+            child.collect_lights(lights);
+        }
+    }
 }
 
 impl Clone for BvhNode {
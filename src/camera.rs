@@ -1,4 +1,5 @@
-use image::{Rgb, RgbImage};
+use core::fmt::{Display, Formatter};
+
 use rayon::ThreadPoolBuilder;
 use rayon::prelude::*;
 use std::sync::{
@@ -7,21 +8,24 @@ use std::sync::{
 };
 
 use crate::bvh::bvh::BvhNode;
-use crate::materials::emission::Emission;
-use crate::utils::functions::degrees_to_radians;
+use crate::integrators::{
+    DEFAULT_ROULETTE_DEPTH, IntegratorKind, Integrators, integrator::Integrator,
+};
+use crate::output::{OutputFormat, RenderError};
+use crate::render_buffer::{AccumulationBuffer, RenderBuffer, Tile};
+use crate::samplers::{SamplerKind, Samplers, sampler::Sampler};
+use crate::tone_mapping::ToneMapping;
+use crate::utils::functions::{degrees_to_radians, mix_seed, random_u64, seed_rng};
 use crate::vector::cross_product;
-use crate::vector::get_random_vector_in_unit_disk;
+use crate::vector::square_to_disk;
 use crate::{
-    materials::{
-        Materials,
-        scatterable::{ScatterRecord, Scatterable},
-    },
-    objects::hittable::Hittable,
-    ray::{Ray, blue_gradient_vertical},
-    utils::{constants::F_INF, functions::random_double, interval::Interval},
+    objects::hittable::{Hittable, HittableList},
+    ray::Ray,
+    utils::{constants::F_INF, interval::Interval},
     vector::{Color, Point, Vector},
 };
 
+#[derive(Clone)]
 pub struct Camera {
     aspect_ratio: f64,
     image_width: u32,
@@ -29,9 +33,11 @@ pub struct Camera {
 
     vertical_fov: f64,
 
-    // NOTE: Unit basis vectors for camera coordinate frame
-    u: Vector,
-    v: Vector,
+    look_from: Point,
+    look_at: Point,
+    v_up: Vector,
+
+    // NOTE: Points back from the scene towards the camera
     w: Vector,
 
     defocus_angle: f64,
@@ -39,9 +45,6 @@ pub struct Camera {
     defocus_disk_u: Vector,
     defocus_disk_v: Vector,
 
-    viewport_width: f64,
-    viewport_height: f64,
-
     centre: Point,
     pixel00_loc: Point,
     pixel_delta_u: Vector,
@@ -49,392 +52,787 @@ pub struct Camera {
 
     samples_per_pixel: u32,
     max_depth: u32,
+    // NOTE: Bounces before Russian roulette can end a path
+    roulette_depth: u32,
+
+    // NOTE: Adaptive sampling, off while noise_threshold is 0. Every pixel gets at
+    // least min_samples and at most samples_per_pixel samples.
+    min_samples: u32,
+    noise_threshold: f64,
+
+    sampler: SamplerKind,
+    integrator: IntegratorKind,
+    // NOTE: The render seed, drawn at random for every render when there's none
+    seed: Option<u64>,
 
     background: Color,
+
+    num_threads: usize,
+    // NOTE: Width and height in pixels of the square tiles the image is split into
+    tile_size: u32,
+
+    tone_mapping: ToneMapping,
+
+    // NOTE: Focus on whatever is in the middle of the image instead of focus_dist
+    auto_focus: bool,
 }
 
-impl Camera {
-    pub fn override_image_specs(&self, aspect_ratio: f64, image_width: u32) -> Camera {
-        let centre = self.centre;
-        let u = self.u;
-        let v = self.v;
-        let w = self.w;
-        let vertical_fov = self.vertical_fov;
-        let samples_per_pixel = self.samples_per_pixel;
-        let max_depth = self.max_depth;
-        let defocus_angle = self.defocus_angle;
-        let focus_dist = self.focus_dist;
-        let defocus_disk_u = self.defocus_disk_u;
-        let defocus_disk_v = self.defocus_disk_v;
-        let background = self.background;
-
-        let image_height: u32 = (image_width as f64 / aspect_ratio) as u32;
-
-        let theta_rad: f64 = degrees_to_radians(vertical_fov);
-        let height: f64 = f64::tan(theta_rad / 2.0);
+#[derive(Debug, PartialEq)]
+pub enum CameraError {
+    InvalidAspectRatio(f64),
+    InvalidImageSize { width: u32, height: u32 },
+    InvalidFieldOfView(f64),
+    InvalidDefocusAngle(f64),
+    InvalidFocusDistance(f64),
+    DegenerateView,
+    NoSamples,
+    NoBounces,
+    InvalidNoiseThreshold(f64),
+    InvalidMinSamples(u32),
+    NoThreads,
+    NoTileSize,
+}
 
-        let viewport_height: f64 = 2.0 * height * focus_dist;
-        let viewport_width: f64 = viewport_height * aspect_ratio;
+impl Display for CameraError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CameraError::InvalidAspectRatio(aspect_ratio) => {
+                write!(f, "Aspect ratio must be positive, got {}", aspect_ratio)
+            }
+            CameraError::InvalidImageSize { width, height } => write!(
+                f,
+                "Image must be at least one pixel in each direction, got {}x{}",
+                width, height
+            ),
+            CameraError::InvalidFieldOfView(vertical_fov) => write!(
+                f,
+                "Vertical field of view must be between 0 and 180 degrees, got {}",
+                vertical_fov
+            ),
+            CameraError::InvalidDefocusAngle(defocus_angle) => write!(
+                f,
+                "Defocus angle must be between 0 and 180 degrees, got {}",
+                defocus_angle
+            ),
+            CameraError::InvalidFocusDistance(focus_dist) => {
+                write!(f, "Focus distance must be positive, got {}", focus_dist)
+            }
+            CameraError::DegenerateView => write!(
+                f,
+                "look_from and look_at must differ and v_up must not point along the view"
+            ),
+            CameraError::NoSamples => write!(f, "Samples per pixel must be at least 1"),
+            CameraError::NoBounces => write!(f, "Max depth must be at least 1"),
+            CameraError::InvalidNoiseThreshold(noise_threshold) => write!(
+                f,
+                "Noise threshold must be 0 (off) or positive, got {}",
+                noise_threshold
+            ),
+            CameraError::InvalidMinSamples(min_samples) => write!(
+                f,
+                "Minimum samples must be between 2 and samples per pixel, got {}",
+                min_samples
+            ),
+            CameraError::NoThreads => write!(f, "Number of threads must be at least 1"),
+            CameraError::NoTileSize => write!(f, "Tile size must be at least 1 pixel"),
+        }
+    }
+}
 
-        let viewport_u: Vector = u.scale(viewport_width);
-        let viewport_v: Vector = v.negate().scale(viewport_height);
+impl std::error::Error for CameraError {}
 
-        let pixel_delta_u: Vector = viewport_u.scale(1.0 / image_width as f64);
-        let pixel_delta_v: Vector = viewport_v.scale(1.0 / image_height as f64);
+// NOTE: Only holds what can be chosen, everything else in the Camera is derived from
+// it in build(), so changing one setting can never leave the rest out of date
+#[derive(Clone)]
+pub struct CameraBuilder {
+    aspect_ratio: f64,
+    image_width: u32,
 
-        let viewport_upper_left = centre
-            .subv(viewport_u.scale(0.5))
-            .subv(viewport_v.scale(0.5))
-            .subv(w.scale(focus_dist));
+    vertical_fov: f64,
 
-        let pixel00_loc: Point =
-            viewport_upper_left.addv(pixel_delta_u.addv(pixel_delta_v).scale(0.5));
+    look_from: Point,
+    look_at: Point,
+    v_up: Vector,
 
-        Camera {
-            aspect_ratio,
-            image_width,
-            image_height,
+    defocus_angle: f64,
+    focus_dist: f64,
 
-            vertical_fov,
+    samples_per_pixel: u32,
+    max_depth: u32,
+    roulette_depth: u32,
 
-            u,
-            v,
-            w,
+    min_samples: u32,
+    noise_threshold: f64,
 
-            defocus_angle,
-            focus_dist,
-            defocus_disk_u,
-            defocus_disk_v,
+    sampler: SamplerKind,
+    integrator: IntegratorKind,
+    seed: Option<u64>,
 
-            viewport_width,
-            viewport_height,
+    background: Color,
 
-            centre,
-            pixel00_loc,
-            pixel_delta_u,
-            pixel_delta_v,
+    num_threads: usize,
+    tile_size: u32,
+
+    tone_mapping: ToneMapping,
+
+    auto_focus: bool,
+}
+
+impl CameraBuilder {
+    pub fn new() -> CameraBuilder {
+        CameraBuilder {
+            aspect_ratio: 16.0 / 9.0,
+            image_width: 400,
+
+            vertical_fov: 40.0,
 
-            samples_per_pixel,
-            max_depth,
+            // NOTE: Default camera will be straight on
+            look_from: Point::new(0.0, 0.0, 1.0),
+            look_at: Point::new(0.0, 0.0, -1.0),
+            v_up: Vector::new(0.0, 1.0, 0.0),
 
-            background,
+            defocus_angle: 0.0,
+            focus_dist: 10.0,
+
+            samples_per_pixel: 250,
+            max_depth: 50,
+            roulette_depth: DEFAULT_ROULETTE_DEPTH,
+
+            min_samples: 16,
+            noise_threshold: 0.0,
+
+            sampler: SamplerKind::Independent,
+            integrator: IntegratorKind::Path,
+            seed: None,
+
+            // NOTE: By default, give a flat blue 'sky' background
+            background: Color::new(0.7, 0.8, 1.0),
+
+            // NOTE: Use every core unless told otherwise
+            num_threads: std::thread::available_parallelism()
+                .map(|cores| cores.get())
+                .unwrap_or(1),
+            tile_size: 32,
+
+            tone_mapping: ToneMapping::default(),
+
+            auto_focus: false,
         }
     }
 
-    pub fn override_camera_pos(
-        &self,
-        look_from: Vector,
-        look_at: Vector,
-        v_up: Vector,
-        vertical_fov: f64,
-        defocus_angle: f64,
-        focus_dist: f64,
-    ) -> Camera {
-        let aspect_ratio = self.aspect_ratio;
-        let image_width = self.image_width;
-        let image_height = self.image_height;
-        let samples_per_pixel = self.samples_per_pixel;
-        let max_depth = self.max_depth;
-        let background = self.background;
-
-        let w = look_from.subv(look_at).unit();
-        let u = cross_product(v_up, w).unit();
+    pub fn aspect_ratio(mut self, aspect_ratio: f64) -> CameraBuilder {
+        self.aspect_ratio = aspect_ratio;
+        self
+    }
+
+    pub fn image_width(mut self, image_width: u32) -> CameraBuilder {
+        self.image_width = image_width;
+        self
+    }
+
+    pub fn vertical_fov(mut self, vertical_fov: f64) -> CameraBuilder {
+        self.vertical_fov = vertical_fov;
+        self
+    }
+
+    pub fn look_from(mut self, look_from: Point) -> CameraBuilder {
+        self.look_from = look_from;
+        self
+    }
+
+    pub fn look_at(mut self, look_at: Point) -> CameraBuilder {
+        self.look_at = look_at;
+        self
+    }
+
+    pub fn v_up(mut self, v_up: Vector) -> CameraBuilder {
+        self.v_up = v_up;
+        self
+    }
+
+    pub fn defocus_angle(mut self, defocus_angle: f64) -> CameraBuilder {
+        self.defocus_angle = defocus_angle;
+        self
+    }
+
+    pub fn focus_dist(mut self, focus_dist: f64) -> CameraBuilder {
+        self.focus_dist = focus_dist;
+        self
+    }
+
+    pub fn samples_per_pixel(mut self, samples_per_pixel: u32) -> CameraBuilder {
+        self.samples_per_pixel = samples_per_pixel;
+        self
+    }
+
+    pub fn max_depth(mut self, max_depth: u32) -> CameraBuilder {
+        self.max_depth = max_depth;
+        self
+    }
+
+    // NOTE: A noise_threshold of 0 turns adaptive sampling off
+    pub fn adaptive_sampling(mut self, min_samples: u32, noise_threshold: f64) -> CameraBuilder {
+        self.min_samples = min_samples;
+        self.noise_threshold = noise_threshold;
+        self
+    }
+
+    pub fn sampler(mut self, sampler: SamplerKind) -> CameraBuilder {
+        self.sampler = sampler;
+        self
+    }
+
+    // NOTE: Paths are never cut short while roulette_depth is at least max_depth
+    pub fn roulette_depth(mut self, roulette_depth: u32) -> CameraBuilder {
+        self.roulette_depth = roulette_depth;
+        self
+    }
+
+    pub fn integrator(mut self, integrator: IntegratorKind) -> CameraBuilder {
+        self.integrator = integrator;
+        self
+    }
+
+    pub fn seed(mut self, seed: Option<u64>) -> CameraBuilder {
+        self.seed = seed;
+        self
+    }
+
+    pub fn background(mut self, background: Color) -> CameraBuilder {
+        self.background = background;
+        self
+    }
+
+    pub fn num_threads(mut self, num_threads: usize) -> CameraBuilder {
+        self.num_threads = num_threads;
+        self
+    }
+
+    pub fn tile_size(mut self, tile_size: u32) -> CameraBuilder {
+        self.tile_size = tile_size;
+        self
+    }
+
+    pub fn tone_mapping(mut self, tone_mapping: ToneMapping) -> CameraBuilder {
+        self.tone_mapping = tone_mapping;
+        self
+    }
+
+    pub fn auto_focus(mut self, auto_focus: bool) -> CameraBuilder {
+        self.auto_focus = auto_focus;
+        self
+    }
+
+    pub fn get_tone_mapping(&self) -> ToneMapping {
+        self.tone_mapping
+    }
+
+    // NOTE: Returns (min_samples, noise_threshold)
+    pub fn get_adaptive_sampling(&self) -> (u32, f64) {
+        (self.min_samples, self.noise_threshold)
+    }
+
+    pub fn validate(&self) -> Result<(), CameraError> {
+        if !(self.aspect_ratio.is_finite() && self.aspect_ratio > 0.0) {
+            return Err(CameraError::InvalidAspectRatio(self.aspect_ratio));
+        }
+
+        let image_height = self.get_image_height();
+        if self.image_width == 0 || image_height == 0 {
+            return Err(CameraError::InvalidImageSize {
+                width: self.image_width,
+                height: image_height,
+            });
+        }
+
+        if !(self.vertical_fov > 0.0 && self.vertical_fov < 180.0) {
+            return Err(CameraError::InvalidFieldOfView(self.vertical_fov));
+        }
+        if !(self.defocus_angle >= 0.0 && self.defocus_angle < 180.0) {
+            return Err(CameraError::InvalidDefocusAngle(self.defocus_angle));
+        }
+        if !(self.focus_dist.is_finite() && self.focus_dist > 0.0) {
+            return Err(CameraError::InvalidFocusDistance(self.focus_dist));
+        }
+
+        let view = self.look_from.subv(self.look_at);
+        if view.near_zero() || cross_product(self.v_up, view).near_zero() {
+            return Err(CameraError::DegenerateView);
+        }
+
+        if self.samples_per_pixel == 0 {
+            return Err(CameraError::NoSamples);
+        }
+        if self.max_depth == 0 {
+            return Err(CameraError::NoBounces);
+        }
+        if !(self.noise_threshold.is_finite() && self.noise_threshold >= 0.0) {
+            return Err(CameraError::InvalidNoiseThreshold(self.noise_threshold));
+        }
+        // NOTE: The variance needs at least two samples
+        if self.noise_threshold > 0.0
+            && (self.min_samples < 2 || self.min_samples > self.samples_per_pixel)
+        {
+            return Err(CameraError::InvalidMinSamples(self.min_samples));
+        }
+        if self.num_threads == 0 {
+            return Err(CameraError::NoThreads);
+        }
+        if self.tile_size == 0 {
+            return Err(CameraError::NoTileSize);
+        }
+
+        Ok(())
+    }
+
+    pub fn build(&self) -> Result<Camera, CameraError> {
+        self.validate()?;
+
+        let image_height = self.get_image_height();
+
+        let w = self.look_from.subv(self.look_at).unit();
+        let u = cross_product(self.v_up, w).unit();
         let v = cross_product(w, u);
 
-        let defocus_radius: f64 = focus_dist * f64::tan(degrees_to_radians(defocus_angle / 2.0));
+        let defocus_radius: f64 =
+            self.focus_dist * f64::tan(degrees_to_radians(self.defocus_angle / 2.0));
         let defocus_disk_u: Vector = u.scale(defocus_radius);
         let defocus_disk_v: Vector = v.scale(defocus_radius);
 
-        let theta_rad: f64 = degrees_to_radians(vertical_fov);
+        let theta_rad: f64 = degrees_to_radians(self.vertical_fov);
         let height: f64 = f64::tan(theta_rad / 2.0);
 
-        let viewport_height: f64 = 2.0 * height * focus_dist;
-        let viewport_width: f64 = viewport_height * aspect_ratio;
+        // NOTE: Uses the real ratio of the image rather than aspect_ratio, since the
+        // height was rounded down to a whole number of pixels
+        let viewport_height: f64 = 2.0 * height * self.focus_dist;
+        let viewport_width: f64 = viewport_height * (self.image_width as f64 / image_height as f64);
 
-        let centre = look_from;
+        let centre = self.look_from;
         let viewport_u: Vector = u.scale(viewport_width);
         let viewport_v: Vector = v.negate().scale(viewport_height);
 
-        let pixel_delta_u: Vector = viewport_u.scale(1.0 / image_width as f64);
+        let pixel_delta_u: Vector = viewport_u.scale(1.0 / self.image_width as f64);
         let pixel_delta_v: Vector = viewport_v.scale(1.0 / image_height as f64);
 
         let viewport_upper_left = centre
             .subv(viewport_u.scale(0.5))
             .subv(viewport_v.scale(0.5))
-            .subv(w.scale(focus_dist));
+            .subv(w.scale(self.focus_dist));
 
         let pixel00_loc: Point =
             viewport_upper_left.addv(pixel_delta_u.addv(pixel_delta_v).scale(0.5));
 
-        Camera {
-            aspect_ratio,
-            image_width,
+        Ok(Camera {
+            aspect_ratio: self.aspect_ratio,
+            image_width: self.image_width,
             image_height,
 
-            vertical_fov,
+            vertical_fov: self.vertical_fov,
+
+            look_from: self.look_from,
+            look_at: self.look_at,
+            v_up: self.v_up,
 
-            u,
-            v,
             w,
 
-            defocus_angle,
-            focus_dist,
+            defocus_angle: self.defocus_angle,
+            focus_dist: self.focus_dist,
             defocus_disk_u,
             defocus_disk_v,
 
-            viewport_width,
-            viewport_height,
-
             centre,
             pixel00_loc,
             pixel_delta_u,
             pixel_delta_v,
 
-            samples_per_pixel,
-            max_depth,
+            samples_per_pixel: self.samples_per_pixel,
+            max_depth: self.max_depth,
+            roulette_depth: self.roulette_depth,
 
-            background,
-        }
+            min_samples: self.min_samples,
+            noise_threshold: self.noise_threshold,
+
+            sampler: self.sampler,
+            integrator: self.integrator,
+            seed: self.seed,
+
+            background: self.background,
+
+            num_threads: self.num_threads,
+            tile_size: self.tile_size,
+
+            tone_mapping: self.tone_mapping,
+
+            auto_focus: self.auto_focus,
+        })
     }
 
-    pub fn override_sampling_specs(&self, samples_per_pixel: u32, max_depth: u32) -> Camera {
-        let aspect_ratio = self.aspect_ratio;
-        let image_width = self.image_width;
-        let image_height = self.image_height;
-        let vertical_fov = self.vertical_fov;
-        let u = self.u;
-        let v = self.v;
-        let w = self.w;
-        let viewport_width = self.viewport_width;
-        let viewport_height = self.viewport_height;
-        let centre = self.centre;
-        let pixel00_loc = self.pixel00_loc;
-        let pixel_delta_u = self.pixel_delta_u;
-        let pixel_delta_v = self.pixel_delta_v;
-        let defocus_angle = self.defocus_angle;
-        let focus_dist = self.focus_dist;
-        let defocus_disk_u = self.defocus_disk_u;
-        let defocus_disk_v = self.defocus_disk_v;
-        let background = self.background;
-
-        Camera {
-            aspect_ratio,
-            image_width,
-            image_height,
+    fn get_image_height(&self) -> u32 {
+        (self.image_width as f64 / self.aspect_ratio) as u32
+    }
+}
 
-            vertical_fov,
+impl Default for CameraBuilder {
+    fn default() -> Self {
+        CameraBuilder::new()
+    }
+}
 
-            u,
-            v,
-            w,
+impl Camera {
+    // NOTE: Starts from this camera's settings, for tweaking an existing camera
+    pub fn to_builder(&self) -> CameraBuilder {
+        CameraBuilder {
+            aspect_ratio: self.aspect_ratio,
+            image_width: self.image_width,
 
-            defocus_angle,
-            focus_dist,
-            defocus_disk_u,
-            defocus_disk_v,
+            vertical_fov: self.vertical_fov,
 
-            viewport_width,
-            viewport_height,
+            look_from: self.look_from,
+            look_at: self.look_at,
+            v_up: self.v_up,
 
-            centre,
-            pixel00_loc,
-            pixel_delta_u,
-            pixel_delta_v,
+            defocus_angle: self.defocus_angle,
+            focus_dist: self.focus_dist,
+
+            samples_per_pixel: self.samples_per_pixel,
+            max_depth: self.max_depth,
+            roulette_depth: self.roulette_depth,
+
+            min_samples: self.min_samples,
+            noise_threshold: self.noise_threshold,
+
+            sampler: self.sampler,
+            integrator: self.integrator,
+            seed: self.seed,
+
+            background: self.background,
 
-            samples_per_pixel,
-            max_depth,
+            num_threads: self.num_threads,
+            tile_size: self.tile_size,
 
-            background,
+            tone_mapping: self.tone_mapping,
+
+            auto_focus: self.auto_focus,
         }
     }
 
-    pub fn set_background(&mut self, background: Color) {
-        self.background = background;
+    pub fn get_tone_mapping(&self) -> ToneMapping {
+        self.tone_mapping
     }
 
-    pub fn render(&self, world: BvhNode) {
-        ThreadPoolBuilder::new()
-            .num_threads(6)
-            .build_global()
-            .unwrap();
-
-        let img = std::sync::Mutex::new(RgbImage::new(self.image_width, self.image_height));
-        let lines_done = Arc::new(AtomicUsize::new(0));
-
-        (0..self.image_height).into_par_iter().for_each(|i| {
-            for j in 0..self.image_width {
-                let mut pixel_color = Color::new(0.0, 0.0, 0.0);
+    pub fn get_auto_focus(&self) -> bool {
+        self.auto_focus
+    }
 
-                for _ in 0..self.samples_per_pixel {
-                    let ray = self.get_ray(i, j);
-                    let color = self.ray_color(ray, &world, self.max_depth);
-                    pixel_color = pixel_color.addv(color);
-                }
+    // NOTE: Moves the plane of focus to the first thing hit by a ray straight through
+    // the centre of the image, or leaves the camera as is if that ray hits nothing
+    pub fn focus_on(&self, world: &dyn Hittable) -> Camera {
+        let ray = Ray::new(self.centre, self.w.negate(), Some(0.0));
+
+        match world.hit(&ray, &Interval::new(0.001, F_INF)) {
+            Some(hit) => self
+                .to_builder()
+                .focus_dist(hit.get_t())
+                .build()
+                .unwrap_or_else(|_| self.clone()),
+            None => self.clone(),
+        }
+    }
 
-                pixel_color = pixel_color.scale(1.0 / self.samples_per_pixel as f64);
-                let mut img_lock = img.lock().unwrap();
-                img_lock.put_pixel(j, i, Rgb(pixel_color.to_color()));
-            }
+    pub fn get_image_specs(&self) -> (f64, u32) {
+        (self.aspect_ratio, self.image_width)
+    }
 
-            let done = lines_done.fetch_add(1, Ordering::Relaxed) + 1;
-            eprintln!(
-                "Current Progress: {}/{} lines done",
-                done, self.image_height
-            );
-        });
+    // NOTE: The height is worked out from the width and aspect ratio
+    pub fn get_image_size(&self) -> (u32, u32) {
+        (self.image_width, self.image_height)
+    }
 
-        // Save the image after all threads finish
-        match img.into_inner().unwrap().save("image.png") {
-            Ok(_) => println!("successfully saved"),
-            Err(e) => println!("{}", e),
-        };
+    pub fn get_sampling_specs(&self) -> (u32, u32) {
+        (self.samples_per_pixel, self.max_depth)
     }
 
-    fn get_ray(&self, i: u32, j: u32) -> Ray {
-        let sample_square: Vector = self.sample_square();
-        let (offset_x, offset_y, _) = sample_square.get_point();
+    // NOTE: Returns (look_from, look_at, v_up, vertical_fov, defocus_angle, focus_dist)
+    pub fn get_camera_pos(&self) -> (Point, Point, Vector, f64, f64, f64) {
+        (
+            self.look_from,
+            self.look_at,
+            self.v_up,
+            self.vertical_fov,
+            self.defocus_angle,
+            self.focus_dist,
+        )
+    }
 
-        let sample_pixel_centre = self
-            .pixel00_loc
-            .addv(self.pixel_delta_u.scale(j as f64 + offset_x))
-            .addv(self.pixel_delta_v.scale(i as f64 + offset_y));
+    // NOTE: Returns (min_samples, noise_threshold)
+    pub fn get_adaptive_sampling(&self) -> (u32, f64) {
+        (self.min_samples, self.noise_threshold)
+    }
 
-        let ray_origin = self.centre;
+    pub fn get_sampler(&self) -> SamplerKind {
+        self.sampler
+    }
 
-        // NOTE: For defocus stuff
-        // let ray_origin = if self.defocus_angle <= 0.0 {
-        //     self.centre
-        // } else {
-        //     self.defocus_disk_sample()
-        // };
+    pub fn get_roulette_depth(&self) -> u32 {
+        self.roulette_depth
+    }
 
-        let ray_direction = sample_pixel_centre.subv(ray_origin);
-        let ray_time = random_double();
+    pub fn get_integrator(&self) -> IntegratorKind {
+        self.integrator
+    }
 
-        Ray::new(ray_origin, ray_direction, Some(ray_time))
+    pub fn get_seed(&self) -> Option<u64> {
+        self.seed
     }
 
-    fn defocus_disk_sample(&self) -> Point {
-        let point = get_random_vector_in_unit_disk();
-        let (x, y, _) = point.get_point();
+    pub fn get_background(&self) -> Color {
+        self.background
+    }
 
-        self.centre
-            .addv(self.defocus_disk_u.scale(x))
-            .addv(self.defocus_disk_v.scale(y))
+    pub fn render(
+        &self,
+        world: BvhNode,
+        lights: HittableList,
+        output_path: &str,
+        format: OutputFormat,
+    ) -> Result<(), RenderError> {
+        self.render_to_buffer(&world, &lights)
+            .save(output_path, format, &self.tone_mapping)
     }
 
-    fn sample_square(&self) -> Vector {
-        Vector::new(random_double() - 0.5, random_double() - 0.5, 0.0)
+    // NOTE: lights are the emissive objects in world, which get sampled directly
+    pub fn render_to_buffer(&self, world: &BvhNode, lights: &HittableList) -> RenderBuffer {
+        self.render_progressive(world, lights, self.samples_per_pixel, |_, _| {})
     }
 
-    fn ray_color(&self, ray: Ray, world: &BvhNode, depth: u32) -> Color {
-        if depth == 0 {
-            self.background
-        } else {
-            let world_interval: Interval = Interval::new(0.001, F_INF);
-            let hit_record = world.hit(&ray, &world_interval);
-
-            match hit_record {
-                Some(hit) => {
-                    let material: Materials = hit.get_material();
-                    let (u, v) = hit.get_texture_coordinates();
-                    let hit_point = hit.get_point();
-
-                    let emission_color = material.emit(u, v, hit_point);
-
-                    match material.scatter(ray, hit) {
-                        Some(scatter) => self
-                            .ray_color(scatter.get_ray(), world, depth - 1)
-                            .multiply(scatter.get_attenuation())
-                            .addv(emission_color),
-                        None => emission_color,
-                    }
-                }
-                None => self.background,
-            }
-        }
+    // NOTE: Takes samples_per_pass samples for every pixel at a time until
+    // samples_per_pixel is reached, handing the running totals and the number of
+    // samples taken so far to on_pass after each pass (e.g. to save a preview)
+    pub fn render_progressive<F>(
+        &self,
+        world: &BvhNode,
+        lights: &HittableList,
+        samples_per_pass: u32,
+        on_pass: F,
+    ) -> RenderBuffer
+    where
+        F: FnMut(&AccumulationBuffer, u32),
+    {
+        let accumulator = AccumulationBuffer::new(self.image_width, self.image_height);
+        let render_seed = self.seed.unwrap_or_else(random_u64);
+
+        self.resume_progressive(
+            world,
+            lights,
+            accumulator,
+            0,
+            samples_per_pass,
+            render_seed,
+            on_pass,
+        )
     }
-}
 
-impl Default for Camera {
-    fn default() -> Self {
-        let aspect_ratio = 16.0 / 9.0;
-        let image_width: u32 = 400;
-        let image_height: u32 = (image_width as f64 / aspect_ratio) as u32;
+    // NOTE: Carries on a progressive render from totals that already hold samples_done
+    // samples per pixel, e.g. ones loaded from a checkpoint. render_seed has to be the
+    // one the render started with for the rest to carry on the same way.
+    #[allow(clippy::too_many_arguments)]
+    pub fn resume_progressive<F>(
+        &self,
+        world: &BvhNode,
+        lights: &HittableList,
+        mut accumulator: AccumulationBuffer,
+        mut samples_done: u32,
+        samples_per_pass: u32,
+        render_seed: u64,
+        mut on_pass: F,
+    ) -> RenderBuffer
+    where
+        F: FnMut(&AccumulationBuffer, u32),
+    {
+        if self.auto_focus {
+            let mut camera = self.focus_on(world);
+            camera.auto_focus = false;
+
+            return camera.resume_progressive(
+                world,
+                lights,
+                accumulator,
+                samples_done,
+                samples_per_pass,
+                render_seed,
+                on_pass,
+            );
+        }
 
-        // NOTE: Default camera will be straight on
-        let look_from = Point::new(0.0, 0.0, 1.0);
-        let look_at = Point::new(0.0, 0.0, -1.0);
-        let v_up = Point::new(0.0, 1.0, 0.0);
+        // NOTE: A pool of our own rather than the global one, which can only be set up
+        // once per process and would stop a second render from picking its thread count
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(self.num_threads)
+            .build()
+            .expect("Could not start the render threads");
+
+        while samples_done < self.samples_per_pixel {
+            let samples = samples_per_pass.min(self.samples_per_pixel - samples_done);
+            pool.install(|| {
+                self.render_pass(world, lights, &mut accumulator, samples, render_seed)
+            });
+            samples_done += samples;
+
+            on_pass(&accumulator, samples_done);
+        }
 
-        let vertical_fov = 40.0;
+        if self.noise_threshold > 0.0 {
+            let num_pixels = (self.image_width * self.image_height) as f64;
+            eprintln!(
+                "Adaptive sampling took {:.1} samples per pixel on average",
+                accumulator.get_total_samples() as f64 / num_pixels
+            );
+        }
 
-        let w = look_from.subv(look_at).unit();
-        let u = cross_product(v_up, w).unit();
-        let v = cross_product(w, u);
+        accumulator.to_render_buffer()
+    }
 
-        let theta_rad: f64 = degrees_to_radians(vertical_fov);
-        let height: f64 = f64::tan(theta_rad / 2.0);
+    fn render_pass(
+        &self,
+        world: &BvhNode,
+        lights: &HittableList,
+        accumulator: &mut AccumulationBuffer,
+        samples: u32,
+        render_seed: u64,
+    ) {
+        let tiles = accumulator.get_tiles(self.tile_size);
+        let totals = &*accumulator;
+        let num_tiles = tiles.len();
+        let tiles_done = Arc::new(AtomicUsize::new(0));
+
+        let tiles: Vec<Tile> = tiles
+            .into_par_iter()
+            .map(|mut tile| {
+                self.render_tile(&mut tile, world, lights, samples, totals, render_seed);
+
+                let done = tiles_done.fetch_add(1, Ordering::Relaxed) + 1;
+                eprintln!("Current Progress: {}/{} tiles done", done, num_tiles);
+
+                tile
+            })
+            .collect();
+
+        for tile in &tiles {
+            accumulator.add_tile(tile);
+        }
+    }
 
-        let defocus_angle = 0.0;
-        let focus_dist = 10.0;
+    // NOTE: Takes up to samples more samples for every pixel in the tile, stopping early
+    // on pixels that have converged if adaptive sampling is on
+    fn render_tile(
+        &self,
+        tile: &mut Tile,
+        world: &BvhNode,
+        lights: &HittableList,
+        samples: u32,
+        totals: &AccumulationBuffer,
+        render_seed: u64,
+    ) {
+        let (x, y, width, height) = tile.get_bounds();
+        let integrator = Integrators::new(
+            self.integrator,
+            self.max_depth,
+            self.roulette_depth,
+            self.background,
+        );
+
+        for i in y..y + height {
+            for j in x..x + width {
+                let (mut count, mut luminance_sum, mut luminance_square_sum) =
+                    totals.get_luminance_stats(j, i);
+
+                let mut sampler = Samplers::new(
+                    self.sampler,
+                    self.samples_per_pixel,
+                    mix_seed(&[render_seed, i as u64, j as u64]),
+                );
+                let mut pixel_color = Color::new(0.0, 0.0, 0.0);
+                let mut pixel_square_sum = 0.0;
+                let mut taken = 0;
 
-        let defocus_radius: f64 = focus_dist * f64::tan(degrees_to_radians(defocus_angle / 2.0));
-        let defocus_disk_u: Vector = u.scale(defocus_radius);
-        let defocus_disk_v: Vector = v.scale(defocus_radius);
+                while taken < samples
+                    && !self.is_converged(count, luminance_sum, luminance_square_sum)
+                {
+                    // NOTE: Reseeded from the pixel's position and sample index, so the
+                    // render comes out the same whatever thread or pass a sample is on
+                    seed_rng(mix_seed(&[render_seed, i as u64, j as u64, count as u64]));
 
-        let viewport_height: f64 = 2.0 * height * focus_dist;
-        let viewport_width: f64 = viewport_height * (image_width as f64 / image_height as f64);
+                    sampler.start_sample(count);
+                    let ray = self.get_ray(i, j, &mut sampler);
+                    let color = integrator.get_radiance(ray, world, lights);
+                    let luminance = color.luminance();
 
-        let centre = look_from;
-        let viewport_u: Vector = u.scale(viewport_width);
-        let viewport_v: Vector = v.negate().scale(viewport_height);
+                    pixel_color = pixel_color.addv(color);
+                    pixel_square_sum += luminance * luminance;
+                    taken += 1;
 
-        let pixel_delta_u: Vector = viewport_u.scale(1.0 / image_width as f64);
-        let pixel_delta_v: Vector = viewport_v.scale(1.0 / image_height as f64);
+                    count += 1;
+                    luminance_sum += luminance;
+                    luminance_square_sum += luminance * luminance;
+                }
 
-        let viewport_upper_left = centre
-            .subv(viewport_u.scale(0.5))
-            .subv(viewport_v.scale(0.5))
-            .subv(w.scale(focus_dist));
+                tile.add_samples(j, i, pixel_color, pixel_square_sum, taken);
+            }
+        }
+    }
 
-        let pixel00_loc: Point =
-            viewport_upper_left.addv(pixel_delta_u.addv(pixel_delta_v).scale(0.5));
+    // NOTE: A pixel has converged once the standard error of its mean luminance is
+    // within noise_threshold of the mean. Near-black pixels are held to an absolute
+    // error instead, or they would never get there.
+    fn is_converged(&self, count: u32, luminance_sum: f64, luminance_square_sum: f64) -> bool {
+        if self.noise_threshold <= 0.0 || count < self.min_samples {
+            return false;
+        }
 
-        let samples_per_pixel: u32 = 250;
-        let max_depth: u32 = 50;
+        let n = count as f64;
+        let mean = luminance_sum / n;
+        let variance = ((luminance_square_sum - n * mean * mean) / (n - 1.0)).max(0.0);
+        let standard_error = (variance / n).sqrt();
 
-        // NOTE: By default, give a flat blue 'sky' background
-        let background = Color::new(0.7, 0.8, 1.0);
+        standard_error <= self.noise_threshold * mean.max(0.01)
+    }
 
-        Camera {
-            aspect_ratio,
-            image_width,
-            image_height,
+    fn get_ray(&self, i: u32, j: u32, sampler: &mut Samplers) -> Ray {
+        let (offset_x, offset_y) = sampler.get_2d();
+        let (offset_x, offset_y) = (offset_x - 0.5, offset_y - 0.5);
 
-            vertical_fov,
+        let sample_pixel_centre = self
+            .pixel00_loc
+            .addv(self.pixel_delta_u.scale(j as f64 + offset_x))
+            .addv(self.pixel_delta_v.scale(i as f64 + offset_y));
 
-            u,
-            v,
-            w,
+        // NOTE: Thin lens, rays start anywhere on the defocus disk and meet again on
+        // the plane of focus
+        let ray_origin = if self.defocus_angle <= 0.0 {
+            self.centre
+        } else {
+            self.defocus_disk_sample(sampler.get_2d())
+        };
 
-            defocus_angle,
-            focus_dist,
-            defocus_disk_u,
-            defocus_disk_v,
+        let ray_direction = sample_pixel_centre.subv(ray_origin);
+        let ray_time = sampler.get_1d();
 
-            viewport_width,
-            viewport_height,
+        Ray::new(ray_origin, ray_direction, Some(ray_time))
+    }
 
-            centre,
-            pixel00_loc,
-            pixel_delta_u,
-            pixel_delta_v,
+    fn defocus_disk_sample(&self, (u, v): (f64, f64)) -> Point {
+        let (x, y) = square_to_disk(u, v);
 
-            samples_per_pixel,
-            max_depth,
+        self.centre
+            .addv(self.defocus_disk_u.scale(x))
+            .addv(self.defocus_disk_v.scale(y))
+    }
+}
 
-            background,
-        }
+impl Default for Camera {
+    fn default() -> Self {
+        CameraBuilder::new()
+            .build()
+            .expect("The default camera settings are valid")
     }
 }
@@ -0,0 +1,331 @@
+// NOTE: Saves the running totals of a progressive render so it can be picked up again
+// after being interrupted
+//
+// The file starts with a few text lines, followed by the raw accumulation buffer:
+//
+//   raytracer checkpoint 3
+//   width=<w> height=<h> samples_done=<n> render_seed=<seed> scene_bytes=<len>
+//   <the scene and its camera as a scene file, len bytes>
+//   <w * h sums as three little-endian f64s each>
+//   <w * h sums of squared luminance as little-endian f64s>
+//   <w * h sample counts as little-endian u32s>
+//
+// The scene description is compared on resume so a checkpoint can't be finished off
+// with a different scene or different settings by mistake. The render seed is kept so
+// the rest of the samples carry on the same way even when no seed was given.
+
+use core::fmt::{Display, Formatter, Result};
+use std::{fs, io, str::FromStr};
+
+use crate::{render_buffer::AccumulationBuffer, vector::Color};
+
+const MAGIC: &str = "raytracer checkpoint 3";
+
+pub struct Checkpoint {
+    scene_description: String,
+    render_seed: u64,
+    samples_done: u32,
+    accumulator: AccumulationBuffer,
+}
+
+impl Checkpoint {
+    pub fn new(
+        scene_description: String,
+        render_seed: u64,
+        samples_done: u32,
+        accumulator: AccumulationBuffer,
+    ) -> Checkpoint {
+        Checkpoint {
+            scene_description,
+            render_seed,
+            samples_done,
+            accumulator,
+        }
+    }
+
+    pub fn get_scene_description(&self) -> &str {
+        &self.scene_description
+    }
+
+    pub fn get_render_seed(&self) -> u64 {
+        self.render_seed
+    }
+
+    pub fn get_samples_done(&self) -> u32 {
+        self.samples_done
+    }
+
+    pub fn into_accumulator(self) -> AccumulationBuffer {
+        self.accumulator
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let accumulator = &self.accumulator;
+        let mut bytes = format!(
+            "{}\nwidth={} height={} samples_done={} render_seed={} scene_bytes={}\n",
+            MAGIC,
+            accumulator.get_width(),
+            accumulator.get_height(),
+            self.samples_done,
+            self.render_seed,
+            self.scene_description.len()
+        )
+        .into_bytes();
+
+        bytes.extend_from_slice(self.scene_description.as_bytes());
+
+        for sum in accumulator.get_sums() {
+            let (r, g, b) = sum.get_point();
+            for val in [r, g, b] {
+                bytes.extend_from_slice(&val.to_le_bytes());
+            }
+        }
+        for square_sum in accumulator.get_luminance_square_sums() {
+            bytes.extend_from_slice(&square_sum.to_le_bytes());
+        }
+        for count in accumulator.get_sample_counts() {
+            bytes.extend_from_slice(&count.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> std::result::Result<Checkpoint, String> {
+        let mut reader = ByteReader { bytes, position: 0 };
+
+        if reader.read_line()? != MAGIC {
+            return Err("Not a checkpoint file".to_string());
+        }
+
+        let params: Vec<&str> = reader.read_line()?.split_whitespace().collect();
+        let [width, height, samples_done, render_seed, scene_bytes] = params[..] else {
+            return Err("Malformed header".to_string());
+        };
+
+        let width: u32 = parse_header_value(width, "width")?;
+        let height: u32 = parse_header_value(height, "height")?;
+        let samples_done: u32 = parse_header_value(samples_done, "samples_done")?;
+        let render_seed: u64 = parse_header_value(render_seed, "render_seed")?;
+        let scene_bytes: u32 = parse_header_value(scene_bytes, "scene_bytes")?;
+        let num_pixels = width as usize * height as usize;
+
+        // NOTE: Checked up front so a corrupted header can't ask for a huge allocation
+        let expected_len = num_pixels
+            .checked_mul(4 * size_of::<f64>() + size_of::<u32>())
+            .and_then(|len| len.checked_add(scene_bytes as usize));
+        if expected_len != Some(bytes.len() - reader.position) {
+            return Err(
+                "The size of the file doesn't match the size given in the header".to_string(),
+            );
+        }
+
+        let scene_description = String::from_utf8(reader.read(scene_bytes as usize)?.to_vec())
+            .map_err(|_| "The scene description is not valid UTF-8".to_string())?;
+
+        let mut sums = Vec::with_capacity(num_pixels);
+        for _ in 0..num_pixels {
+            let r = f64::from_le_bytes(reader.read_array()?);
+            let g = f64::from_le_bytes(reader.read_array()?);
+            let b = f64::from_le_bytes(reader.read_array()?);
+            sums.push(Color::new(r, g, b));
+        }
+
+        let mut luminance_square_sums = Vec::with_capacity(num_pixels);
+        for _ in 0..num_pixels {
+            luminance_square_sums.push(f64::from_le_bytes(reader.read_array()?));
+        }
+
+        let mut sample_counts = Vec::with_capacity(num_pixels);
+        for _ in 0..num_pixels {
+            sample_counts.push(u32::from_le_bytes(reader.read_array()?));
+        }
+
+        Ok(Checkpoint {
+            scene_description,
+            render_seed,
+            samples_done,
+            accumulator: AccumulationBuffer::from_parts(
+                width,
+                height,
+                sums,
+                luminance_square_sums,
+                sample_counts,
+            ),
+        })
+    }
+}
+
+fn parse_header_value<T: FromStr>(param: &str, key: &str) -> std::result::Result<T, String> {
+    match param.split_once('=') {
+        Some((name, value)) if name == key => value
+            .parse()
+            .map_err(|_| format!("Invalid value for '{}' in the header", key)),
+        _ => Err(format!("Expected '{}' in the header", key)),
+    }
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn read(&mut self, len: usize) -> std::result::Result<&'a [u8], String> {
+        let end = self.position + len;
+        if end > self.bytes.len() {
+            return Err("The checkpoint file is cut short".to_string());
+        }
+
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+
+        Ok(bytes)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> std::result::Result<[u8; N], String> {
+        Ok(self.read(N)?.try_into().unwrap())
+    }
+
+    fn read_line(&mut self) -> std::result::Result<&'a str, String> {
+        let len = self.bytes[self.position..]
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .ok_or_else(|| "The checkpoint file is cut short".to_string())?;
+
+        let line = self.read(len)?;
+        self.position += 1;
+
+        std::str::from_utf8(line).map_err(|_| "The header is not valid UTF-8".to_string())
+    }
+}
+
+// NOTE: Written next to the checkpoint and renamed over it, so being interrupted while
+// saving never leaves a half-written checkpoint behind
+pub fn save_checkpoint(
+    checkpoint: &Checkpoint,
+    path: &str,
+) -> std::result::Result<(), CheckpointError> {
+    let temp_path = format!("{}.tmp", path);
+
+    fs::write(&temp_path, checkpoint.to_bytes())
+        .and_then(|_| fs::rename(&temp_path, path))
+        .map_err(|error| CheckpointError::Io {
+            path: path.to_string(),
+            error,
+        })
+}
+
+pub fn load_checkpoint(path: &str) -> std::result::Result<Checkpoint, CheckpointError> {
+    let bytes = fs::read(path).map_err(|error| CheckpointError::Io {
+        path: path.to_string(),
+        error,
+    })?;
+
+    Checkpoint::from_bytes(&bytes).map_err(|message| CheckpointError::Invalid {
+        path: path.to_string(),
+        message,
+    })
+}
+
+#[derive(Debug)]
+pub enum CheckpointError {
+    Io { path: String, error: io::Error },
+    Invalid { path: String, message: String },
+    Mismatch { path: String },
+}
+
+impl Display for CheckpointError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            CheckpointError::Io { path, error } => {
+                write!(f, "Could not access checkpoint {}: {}", path, error)
+            }
+            CheckpointError::Invalid { path, message } => {
+                write!(f, "Invalid checkpoint {}: {}", path, message)
+            }
+            CheckpointError::Mismatch { path } => write!(
+                f,
+                "Checkpoint {} was made with a different scene or different render settings",
+                path
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CheckpointError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CheckpointError::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        scene_file::{parser::parse_scene, writer::write_scene},
+        utils::functions::random_u64,
+    };
+
+    const SCENE: &str = "\
+camera width=8 aspect_ratio=1 samples_per_pixel=4 max_depth=4 background=0.5,0.6,0.7
+material ground lambertian color=0.5,0.5,0.5
+material light diffuse_light color=4,4,4
+sphere centre=0,-100.5,-1 radius=100 material=ground
+sphere centre=0,0,-1 radius=0.5 material=light
+";
+
+    // NOTE: The checkpoint from halfway through is what an interrupted render leaves
+    // behind, so finishing from it has to land on the same image
+    #[test]
+    fn resuming_without_a_seed_matches_an_uninterrupted_render() {
+        let scene = parse_scene(SCENE, None).unwrap();
+        assert!(scene.get_camera().get_seed().is_none());
+
+        let (width, height) = scene.get_camera().get_image_size();
+        let render_seed = random_u64();
+        let mut saved = None;
+
+        let uninterrupted = scene.resume_progressive(
+            AccumulationBuffer::new(width, height),
+            0,
+            2,
+            render_seed,
+            |accumulator, samples_done| {
+                if saved.is_none() {
+                    let checkpoint = Checkpoint::new(
+                        write_scene(&scene),
+                        render_seed,
+                        samples_done,
+                        accumulator.clone(),
+                    );
+                    saved = Some(checkpoint.to_bytes());
+                }
+            },
+        );
+
+        let checkpoint = Checkpoint::from_bytes(&saved.unwrap()).unwrap();
+        assert_eq!(checkpoint.get_samples_done(), 2);
+
+        let samples_done = checkpoint.get_samples_done();
+        let render_seed = checkpoint.get_render_seed();
+        let resumed = scene.resume_progressive(
+            checkpoint.into_accumulator(),
+            samples_done,
+            2,
+            render_seed,
+            |_, _| {},
+        );
+
+        for y in 0..height {
+            for x in 0..width {
+                assert_eq!(
+                    resumed.get_pixel(x, y).get_point(),
+                    uninterrupted.get_pixel(x, y).get_point()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,390 @@
+// NOTE: Command-line options for picking a scene and overriding its render settings
+// without having to recompile
+
+use crate::{
+    integrators::IntegratorKind,
+    output::OutputFormat,
+    samplers::SamplerKind,
+    tone_mapping::{ToneMapOperator, TransferCurve},
+};
+
+pub const USAGE: &str = "\
+Usage: raytracingInAMonth [OPTIONS] [SCENE]
+
+Options:
+  -l, --list                     List the registered scenes and exit
+  -s, --scene <NAME>             Scene to render (default: week)
+  -f, --scene-file <PATH>        Render a scene description file instead
+  -w, --width <PIXELS>           Override the image width
+  -a, --aspect-ratio <RATIO>     Override the aspect ratio (e.g. 1.5 or 16/9)
+  -n, --samples-per-pixel <N>    Override the number of samples per pixel
+  -d, --max-depth <N>            Override the maximum ray bounce depth
+      --roulette-depth <N>       Bounces before Russian roulette can end a path
+                                 (default: 3, --max-depth or more turns it off)
+      --noise-threshold <T>      Turn on adaptive sampling, stopping on pixels whose
+                                 relative noise is below T (e.g. 0.01)
+      --min-samples <N>          Samples every pixel gets before adaptive sampling
+                                 can stop (default: 16)
+      --defocus-angle <DEGREES>  Override the depth of field aperture (0 turns it off)
+      --focus-dist <DISTANCE>    Override the distance to the plane of focus
+      --auto-focus               Focus on whatever is in the middle of the image
+      --sampler <SAMPLER>        How camera rays are spread over each pixel: random,
+                                 stratified, halton or sobol
+      --integrator <INTEGRATOR>  How light is gathered along each ray: naive, or path
+                                 for light sampling with MIS (default: path)
+      --seed <N>                 Seed for every random number, so the same seed and
+                                 settings always give the same image (takes the
+                                 place of a scene file's camera seed=)
+  -p, --progressive <N>          Render in passes of N samples per pixel, saving a
+                                 preview to the output path after each pass
+  -c, --checkpoint <PATH>        Save a checkpoint after every pass so the render can
+                                 be resumed (passes are 16 samples unless
+                                 --progressive is given)
+  -r, --resume                   Carry on from the checkpoint given with --checkpoint
+  -t, --threads <N>              Number of render threads (default: all cores)
+      --tile-size <PIXELS>       Size of the square tiles the image is split into
+                                 (default: 32)
+  -o, --output <PATH>            Output image path (default: image.png)
+  -F, --format <FORMAT>          Output image format: png, jpg, ppm, tga, bmp,
+                                 hdr or exr
+                                 (default: taken from the output path)
+      --tone-map <OPERATOR>      Tone mapping operator: none, reinhard or aces
+      --exposure <STOPS>         Exposure adjustment in stops (e.g. 1 or -0.5)
+      --transfer <CURVE>         Display transfer curve: gamma2 or srgb
+  -e, --export <PATH>            Write the scene out as a scene file instead of rendering
+  -h, --help                     Print this help message";
+
+pub const DEFAULT_SCENE: &str = "week";
+pub const DEFAULT_OUTPUT: &str = "image.png";
+pub const DEFAULT_SAMPLES_PER_PASS: u32 = 16;
+
+pub enum CliCommand {
+    Help,
+    ListScenes,
+    Render(Box<RenderOptions>),
+}
+
+pub struct RenderOptions {
+    pub scene: String,
+    pub scene_file: Option<String>,
+    pub image_width: Option<u32>,
+    pub aspect_ratio: Option<f64>,
+    pub samples_per_pixel: Option<u32>,
+    pub max_depth: Option<u32>,
+    pub roulette_depth: Option<u32>,
+    pub noise_threshold: Option<f64>,
+    pub min_samples: Option<u32>,
+    pub defocus_angle: Option<f64>,
+    pub focus_dist: Option<f64>,
+    pub auto_focus: bool,
+    pub sampler: Option<SamplerKind>,
+    pub integrator: Option<IntegratorKind>,
+    pub seed: Option<u64>,
+    pub progressive: Option<u32>,
+    pub checkpoint: Option<String>,
+    pub resume: bool,
+    pub num_threads: Option<usize>,
+    pub tile_size: Option<u32>,
+    pub output: String,
+    pub format: Option<OutputFormat>,
+    pub tone_map: Option<ToneMapOperator>,
+    pub exposure: Option<f64>,
+    pub transfer: Option<TransferCurve>,
+    pub export: Option<String>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            scene: DEFAULT_SCENE.to_string(),
+            scene_file: None,
+            image_width: None,
+            aspect_ratio: None,
+            samples_per_pixel: None,
+            max_depth: None,
+            roulette_depth: None,
+            noise_threshold: None,
+            min_samples: None,
+            defocus_angle: None,
+            focus_dist: None,
+            auto_focus: false,
+            sampler: None,
+            integrator: None,
+            seed: None,
+            progressive: None,
+            checkpoint: None,
+            resume: false,
+            num_threads: None,
+            tile_size: None,
+            output: DEFAULT_OUTPUT.to_string(),
+            format: None,
+            tone_map: None,
+            exposure: None,
+            transfer: None,
+            export: None,
+        }
+    }
+}
+
+pub fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<CliCommand, String> {
+    let mut options = RenderOptions::default();
+    let mut scene_given = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(CliCommand::Help),
+            "-l" | "--list" => return Ok(CliCommand::ListScenes),
+            "-s" | "--scene" => {
+                options.scene = next_value(&mut args, &arg)?;
+                scene_given = true;
+            }
+            "-f" | "--scene-file" => options.scene_file = Some(next_value(&mut args, &arg)?),
+            "-w" | "--width" => {
+                options.image_width = Some(parse_positive(&next_value(&mut args, &arg)?, &arg)?)
+            }
+            "-a" | "--aspect-ratio" => {
+                options.aspect_ratio = Some(parse_aspect_ratio(&next_value(&mut args, &arg)?)?)
+            }
+            "-n" | "--samples-per-pixel" => {
+                options.samples_per_pixel =
+                    Some(parse_positive(&next_value(&mut args, &arg)?, &arg)?)
+            }
+            "-d" | "--max-depth" => {
+                options.max_depth = Some(parse_positive(&next_value(&mut args, &arg)?, &arg)?)
+            }
+            "--roulette-depth" => {
+                let depth = next_value(&mut args, &arg)?;
+                options.roulette_depth = Some(
+                    depth
+                        .parse::<u32>()
+                        .map_err(|_| format!("Invalid roulette depth '{}'", depth))?,
+                );
+            }
+            "--noise-threshold" => {
+                let threshold = next_value(&mut args, &arg)?;
+                options.noise_threshold = match threshold.parse::<f64>() {
+                    Ok(parsed) if parsed.is_finite() && parsed >= 0.0 => Some(parsed),
+                    _ => return Err(format!("Invalid noise threshold '{}'", threshold)),
+                };
+            }
+            "--min-samples" => {
+                options.min_samples = Some(parse_positive(&next_value(&mut args, &arg)?, &arg)?)
+            }
+            "--defocus-angle" => {
+                let angle = next_value(&mut args, &arg)?;
+                options.defocus_angle = match angle.parse::<f64>() {
+                    Ok(parsed) if (0.0..180.0).contains(&parsed) => Some(parsed),
+                    _ => return Err(format!("Invalid defocus angle '{}'", angle)),
+                };
+            }
+            "--focus-dist" => {
+                let dist = next_value(&mut args, &arg)?;
+                options.focus_dist = match dist.parse::<f64>() {
+                    Ok(parsed) if parsed.is_finite() && parsed > 0.0 => Some(parsed),
+                    _ => return Err(format!("Invalid focus distance '{}'", dist)),
+                };
+            }
+            "--auto-focus" => options.auto_focus = true,
+            "--sampler" => {
+                let sampler = next_value(&mut args, &arg)?;
+                options.sampler = Some(
+                    SamplerKind::from_name(&sampler)
+                        .ok_or_else(|| format!("Unknown sampler '{}'", sampler))?,
+                );
+            }
+            "--integrator" => {
+                let integrator = next_value(&mut args, &arg)?;
+                options.integrator = Some(
+                    IntegratorKind::from_name(&integrator)
+                        .ok_or_else(|| format!("Unknown integrator '{}'", integrator))?,
+                );
+            }
+            "--seed" => {
+                let seed = next_value(&mut args, &arg)?;
+                options.seed = Some(
+                    seed.parse::<u64>()
+                        .map_err(|_| format!("Invalid seed '{}'", seed))?,
+                );
+            }
+            "-p" | "--progressive" => {
+                options.progressive = Some(parse_positive(&next_value(&mut args, &arg)?, &arg)?)
+            }
+            "-c" | "--checkpoint" => options.checkpoint = Some(next_value(&mut args, &arg)?),
+            "-r" | "--resume" => options.resume = true,
+            "-t" | "--threads" => {
+                options.num_threads = Some(parse_positive(&next_value(&mut args, &arg)?, &arg)?)
+            }
+            "--tile-size" => {
+                options.tile_size = Some(parse_positive(&next_value(&mut args, &arg)?, &arg)?)
+            }
+            "-o" | "--output" => options.output = next_value(&mut args, &arg)?,
+            "-F" | "--format" => {
+                let format = next_value(&mut args, &arg)?;
+                options.format = Some(
+                    OutputFormat::from_name(&format)
+                        .ok_or_else(|| format!("Unknown image format '{}'", format))?,
+                );
+            }
+            "--tone-map" => {
+                let operator = next_value(&mut args, &arg)?;
+                options.tone_map = Some(
+                    ToneMapOperator::from_name(&operator)
+                        .ok_or_else(|| format!("Unknown tone mapping operator '{}'", operator))?,
+                );
+            }
+            "--exposure" => {
+                let exposure = next_value(&mut args, &arg)?;
+                options.exposure = match exposure.parse::<f64>() {
+                    Ok(parsed) if parsed.is_finite() => Some(parsed),
+                    _ => return Err(format!("Invalid exposure '{}'", exposure)),
+                };
+            }
+            "--transfer" => {
+                let curve = next_value(&mut args, &arg)?;
+                options.transfer = Some(
+                    TransferCurve::from_name(&curve)
+                        .ok_or_else(|| format!("Unknown transfer curve '{}'", curve))?,
+                );
+            }
+            "-e" | "--export" => options.export = Some(next_value(&mut args, &arg)?),
+            flag if flag.starts_with('-') => return Err(format!("Unknown option '{}'", flag)),
+            scene => {
+                if scene_given {
+                    return Err(format!("Unexpected extra argument '{}'", scene));
+                }
+
+                options.scene = scene.to_string();
+                scene_given = true;
+            }
+        }
+    }
+
+    if scene_given && options.scene_file.is_some() {
+        return Err("A scene name and a scene file cannot both be given".to_string());
+    }
+
+    if options.resume && options.checkpoint.is_none() {
+        return Err(
+            "--resume needs the checkpoint to resume from, given with --checkpoint".to_string(),
+        );
+    }
+
+    Ok(CliCommand::Render(Box::new(options)))
+}
+
+fn next_value<I: Iterator<Item = String>>(args: &mut I, flag: &str) -> Result<String, String> {
+    args.next()
+        .ok_or_else(|| format!("Missing value for '{}'", flag))
+}
+
+fn parse_positive<T>(value: &str, flag: &str) -> Result<T, String>
+where
+    T: std::str::FromStr + PartialOrd + Default,
+{
+    match value.parse::<T>() {
+        Ok(parsed) if parsed > T::default() => Ok(parsed),
+        _ => Err(format!(
+            "Expected a positive integer for '{}', got '{}'",
+            flag, value
+        )),
+    }
+}
+
+// NOTE: Accepts either a plain number ("1.5") or a ratio ("16/9")
+fn parse_aspect_ratio(value: &str) -> Result<f64, String> {
+    let ratio = match value.split_once('/') {
+        Some((width, height)) => {
+            match (width.trim().parse::<f64>(), height.trim().parse::<f64>()) {
+                (Ok(width), Ok(height)) if height != 0.0 => Some(width / height),
+                _ => None,
+            }
+        }
+        None => value.parse::<f64>().ok(),
+    };
+
+    match ratio {
+        Some(ratio) if ratio.is_finite() && ratio > 0.0 => Ok(ratio),
+        _ => Err(format!("Invalid aspect ratio '{}'", value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliCommand, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn parse_options(args: &[&str]) -> RenderOptions {
+        match parse(args) {
+            Ok(CliCommand::Render(options)) => *options,
+            Ok(_) => panic!("{:?} should be a render", args),
+            Err(err) => panic!("{:?} should parse: {}", args, err),
+        }
+    }
+
+    fn assert_error(args: &[&str], expected: &str) {
+        match parse(args) {
+            Err(err) => assert!(err.contains(expected), "{}", err),
+            Ok(_) => panic!("{:?} should not parse", args),
+        }
+    }
+
+    #[test]
+    fn scene_name_and_scene_file_conflict() {
+        assert_error(
+            &["cornell_box", "--scene-file", "scenes/cornell_box.scene"],
+            "cannot both be given",
+        );
+        assert_error(
+            &["--scene", "week", "-f", "scenes/cornell_box.scene"],
+            "cannot both be given",
+        );
+
+        let options = parse_options(&["-f", "scenes/cornell_box.scene"]);
+        assert_eq!(
+            options.scene_file.as_deref(),
+            Some("scenes/cornell_box.scene")
+        );
+    }
+
+    #[test]
+    fn resume_needs_a_checkpoint() {
+        assert_error(&["week", "--resume"], "--checkpoint");
+
+        let options = parse_options(&["week", "-c", "week.checkpoint", "-r"]);
+        assert!(options.resume);
+        assert_eq!(options.checkpoint.as_deref(), Some("week.checkpoint"));
+    }
+
+    #[test]
+    fn aspect_ratio_can_be_a_ratio() {
+        let options = parse_options(&["--aspect-ratio", "16/9"]);
+        assert_eq!(options.aspect_ratio, Some(16.0 / 9.0));
+
+        let options = parse_options(&["-a", "1.5"]);
+        assert_eq!(options.aspect_ratio, Some(1.5));
+    }
+
+    #[test]
+    fn zero_and_negative_values_are_rejected() {
+        for flag in ["--width", "--samples-per-pixel", "--max-depth", "--threads"] {
+            assert_error(&[flag, "0"], "positive integer");
+            assert_error(&[flag, "-4"], "positive integer");
+        }
+
+        for ratio in ["0", "-1.5", "16/0", "-16/9", "0/9"] {
+            assert_error(&["--aspect-ratio", ratio], "Invalid aspect ratio");
+        }
+    }
+
+    #[test]
+    fn only_one_positional_scene() {
+        assert_error(&["week", "weekend"], "Unexpected extra argument 'weekend'");
+        assert_error(&["--scene", "week", "weekend"], "Unexpected extra argument");
+
+        assert_eq!(parse_options(&["weekend"]).scene, "weekend");
+        assert_eq!(parse_options(&[]).scene, DEFAULT_SCENE);
+    }
+}
@@ -0,0 +1,73 @@
+pub mod integrator;
+pub mod naive;
+pub mod path;
+
+use crate::{
+    objects::hittable::{Hittable, HittableList},
+    ray::Ray,
+    vector::Color,
+};
+
+use integrator::Integrator;
+use naive::NaiveIntegrator;
+use path::PathIntegrator;
+
+// NOTE: Bounces before Russian roulette can end a path
+pub const DEFAULT_ROULETTE_DEPTH: u32 = 3;
+
+// NOTE: Which integrator the camera works out the light along each ray with
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IntegratorKind {
+    Naive,
+    Path,
+}
+
+impl IntegratorKind {
+    pub fn from_name(name: &str) -> Option<IntegratorKind> {
+        match name.to_ascii_lowercase().as_str() {
+            "naive" => Some(IntegratorKind::Naive),
+            "path" | "mis" => Some(IntegratorKind::Path),
+            _ => None,
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            IntegratorKind::Naive => "naive",
+            IntegratorKind::Path => "path",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Integrators {
+    Naive(NaiveIntegrator),
+    Path(PathIntegrator),
+}
+
+impl Integrators {
+    pub fn new(
+        kind: IntegratorKind,
+        max_depth: u32,
+        roulette_depth: u32,
+        background: Color,
+    ) -> Integrators {
+        match kind {
+            IntegratorKind::Naive => {
+                Integrators::Naive(NaiveIntegrator::new(max_depth, roulette_depth, background))
+            }
+            IntegratorKind::Path => {
+                Integrators::Path(PathIntegrator::new(max_depth, roulette_depth, background))
+            }
+        }
+    }
+}
+
+impl Integrator for Integrators {
+    fn get_radiance(&self, ray: Ray, world: &dyn Hittable, lights: &HittableList) -> Color {
+        match self {
+            Integrators::Naive(integrator) => integrator.get_radiance(ray, world, lights),
+            Integrators::Path(integrator) => integrator.get_radiance(ray, world, lights),
+        }
+    }
+}
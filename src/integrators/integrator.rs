@@ -0,0 +1,60 @@
+use crate::{
+    objects::hittable::{Hittable, HittableList},
+    ray::Ray,
+    utils::functions::random_double,
+    vector::Color,
+};
+
+pub trait Integrator {
+    // NOTE: Light arriving at the ray's origin from along the ray. lights holds the
+    // scene's emitters for integrators that sample them directly.
+    fn get_radiance(&self, ray: Ray, world: &dyn Hittable, lights: &HittableList) -> Color;
+}
+
+// NOTE: Russian roulette, dim paths are stopped at random and the survivors are
+// brightened to make up for it, which keeps the average the same. Returns the
+// throughput to carry on with, or None when the path ends here.
+pub fn russian_roulette(throughput: Color) -> Option<Color> {
+    let (r, g, b) = throughput.get_point();
+    let survival = r.max(g).max(b).min(1.0);
+
+    if random_double() >= survival {
+        None
+    } else {
+        Some(throughput.scale(1.0 / survival))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::functions::seed_rng;
+
+    #[test]
+    fn russian_roulette_keeps_the_average_throughput() {
+        seed_rng(1);
+
+        let throughput = Color::new(0.2, 0.1, 0.05);
+        let runs = 100_000;
+        let total = (0..runs)
+            .filter_map(|_| russian_roulette(throughput))
+            .fold(Color::new(0.0, 0.0, 0.0), |total, survivor| {
+                total.addv(survivor)
+            });
+        let (r, g, b) = total.scale(1.0 / runs as f64).get_point();
+
+        assert!((r - 0.2).abs() < 0.01);
+        assert!((g - 0.1).abs() < 0.005);
+        assert!((b - 0.05).abs() < 0.0025);
+    }
+
+    #[test]
+    fn bright_paths_always_survive_russian_roulette() {
+        let throughput = Color::new(1.5, 0.2, 0.2);
+
+        for _ in 0..100 {
+            let (r, _, _) = russian_roulette(throughput).unwrap().get_point();
+            assert_eq!(r, 1.5);
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use crate::{
+    integrators::integrator::{Integrator, russian_roulette},
+    materials::{
+        Materials,
+        emission::Emission,
+        scatterable::{ScatterRecord, Scatterable},
+    },
+    objects::hittable::{Hittable, HittableList},
+    pdf::pdf::Pdf,
+    ray::Ray,
+    utils::{constants::F_INF, interval::Interval},
+    vector::Color,
+};
+
+// NOTE: The path tracer from the books, every bounce follows the material and light is
+// only found when a bounce happens to hit it. Slow to converge on small lights, but
+// simple enough to check the other integrators against.
+#[derive(Clone, Copy)]
+pub struct NaiveIntegrator {
+    max_depth: u32,
+    roulette_depth: u32,
+    background: Color,
+}
+
+impl NaiveIntegrator {
+    pub fn new(max_depth: u32, roulette_depth: u32, background: Color) -> NaiveIntegrator {
+        NaiveIntegrator {
+            max_depth,
+            roulette_depth,
+            background,
+        }
+    }
+}
+
+impl Integrator for NaiveIntegrator {
+    fn get_radiance(&self, ray: Ray, world: &dyn Hittable, _lights: &HittableList) -> Color {
+        let mut ray = ray;
+        let mut radiance = Color::new(0.0, 0.0, 0.0);
+        let mut throughput = Color::new(1.0, 1.0, 1.0);
+
+        for depth in 0..self.max_depth {
+            let hit = match world.hit(&ray, &Interval::new(0.001, F_INF)) {
+                Some(hit) => hit,
+                None => return radiance.addv(throughput.multiply(self.background)),
+            };
+
+            let material: Materials = hit.get_material();
+            let (u, v) = hit.get_texture_coordinates();
+            let hit_point = hit.get_point();
+
+            let emission_color = material.emit(u, v, hit_point);
+            radiance = radiance.addv(throughput.multiply(emission_color));
+
+            let scatter = match material.scatter(ray, hit.clone()) {
+                Some(scatter) => scatter,
+                None => return radiance,
+            };
+
+            match scatter {
+                ScatterRecord::Specular {
+                    ray: specular_ray,
+                    attenuation,
+                } => {
+                    throughput = throughput.multiply(attenuation);
+                    ray = specular_ray;
+                }
+                ScatterRecord::Sampled(pdf) => {
+                    let Some(direction) = pdf.generate() else {
+                        return radiance;
+                    };
+                    let pdf_value = pdf.get_value(direction);
+                    let bsdf = material.evaluate(&ray, &hit, direction);
+
+                    if pdf_value <= 0.0 || bsdf.is_zero() {
+                        return radiance;
+                    }
+
+                    throughput = throughput.multiply(bsdf).scale(1.0 / pdf_value);
+                    ray = Ray::new(hit_point, direction, Some(ray.get_time()));
+                }
+            }
+
+            // NOTE: Not after the last bounce, so roulette_depth >= max_depth turns it off
+            if depth + 1 >= self.roulette_depth && depth + 1 < self.max_depth {
+                match russian_roulette(throughput) {
+                    Some(survivor) => throughput = survivor,
+                    None => return radiance,
+                }
+            }
+        }
+
+        // NOTE: Out of bounces, the books treat the rest of the path as if it had
+        // escaped to the background
+        radiance.addv(throughput.multiply(self.background))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        materials::lambertian::LambertianMaterial,
+        objects::sphere::Sphere,
+        texture::solid_color::SolidColorTexture,
+        utils::functions::seed_rng,
+        vector::{Point, Vector},
+    };
+
+    // NOTE: Inside a closed sphere that reflects half the light, so every path bounces
+    // until it's stopped and loses exactly half its throughput on each bounce
+    fn radiance_inside_a_sphere(max_depth: u32, roulette_depth: u32) -> f64 {
+        let material = Materials::Lambertian(LambertianMaterial::new(Arc::new(
+            SolidColorTexture::new_from_rgb(0.5, 0.5, 0.5),
+        )));
+        let mut world = HittableList::new();
+        world.add_hittable(Arc::new(Sphere::new(
+            Point::new(0.0, 0.0, 0.0),
+            10.0,
+            material,
+        )));
+
+        let integrator = NaiveIntegrator::new(max_depth, roulette_depth, Color::new(1.0, 1.0, 1.0));
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0), None);
+
+        integrator
+            .get_radiance(ray, &world, &HittableList::new())
+            .get_point()
+            .0
+    }
+
+    #[test]
+    fn paths_out_of_bounces_see_the_background() {
+        seed_rng(1);
+
+        // NOTE: Four bounces, each halving what reaches the background
+        for _ in 0..100 {
+            assert!((radiance_inside_a_sphere(4, 4) - 0.0625).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn roulette_only_starts_before_max_depth() {
+        seed_rng(1);
+
+        for _ in 0..100 {
+            assert!((radiance_inside_a_sphere(4, 10) - 0.0625).abs() < 1e-9);
+        }
+
+        // NOTE: Starting on the first bounce, paths either end with nothing or survive
+        // the first three brightened back up to 1, then lose half on the last one
+        let results: Vec<f64> = (0..100).map(|_| radiance_inside_a_sphere(4, 0)).collect();
+        assert!(results.iter().all(|&r| r == 0.0 || (r - 0.5).abs() < 1e-9));
+        assert!(results.contains(&0.0));
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{
+    integrators::integrator::{Integrator, russian_roulette},
+    materials::{
+        Materials,
+        emission::Emission,
+        scatterable::{ScatterRecord, Scatterable},
+    },
+    objects::hittable::{HitRecord, Hittable, HittableList},
+    pdf::{Pdfs, hittable::HittablePdf, pdf::Pdf, power_heuristic},
+    ray::Ray,
+    utils::{constants::F_INF, interval::Interval},
+    vector::Color,
+};
+
+// NOTE: Follows each path in a loop, carrying how much of the light found further on
+// still makes it back to the camera (the throughput). Every diffuse bounce takes one
+// sample towards the lights and one from the material, weighted against each other
+// with multiple importance sampling, so both small lights and glossy reflections of
+// big ones converge quickly. After roulette_depth bounces paths are cut short at
+// random depending on their throughput.
+#[derive(Clone, Copy)]
+pub struct PathIntegrator {
+    max_depth: u32,
+    roulette_depth: u32,
+    background: Color,
+}
+
+impl PathIntegrator {
+    pub fn new(max_depth: u32, roulette_depth: u32, background: Color) -> PathIntegrator {
+        PathIntegrator {
+            max_depth,
+            roulette_depth,
+            background,
+        }
+    }
+
+    // NOTE: Light reaching the hit straight from a random point on the lights and
+    // scattered along the ray, weighted against the chance the material would have
+    // scattered that way itself
+    fn sample_lights(
+        &self,
+        ray: Ray,
+        hit: &HitRecord,
+        scatter_pdf: &Pdfs,
+        world: &dyn Hittable,
+        lights: &HittableList,
+    ) -> Color {
+        if lights.get_num_hittables() == 0 {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+
+        let light_pdf = HittablePdf::new(lights, hit.get_point(), ray.get_time());
+        let Some(direction) = light_pdf.generate() else {
+            return Color::new(0.0, 0.0, 0.0);
+        };
+        let light_pdf_value = light_pdf.get_value(direction);
+        let bsdf = hit.get_material().evaluate(&ray, hit, direction);
+
+        if bsdf.is_zero() || light_pdf_value <= 0.0 {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+
+        // NOTE: Whatever the shadow ray hits first, so anything in the way blocks it
+        let shadow_ray = Ray::new(hit.get_point(), direction, Some(ray.get_time()));
+        let emitted = match world.hit(&shadow_ray, &Interval::new(0.001, F_INF)) {
+            Some(light_hit) => {
+                let (u, v) = light_hit.get_texture_coordinates();
+                light_hit.get_material().emit(u, v, light_hit.get_point())
+            }
+            None => return Color::new(0.0, 0.0, 0.0),
+        };
+
+        let weight = power_heuristic(light_pdf_value, scatter_pdf.get_value(direction));
+
+        emitted.multiply(bsdf).scale(weight / light_pdf_value)
+    }
+}
+
+impl Integrator for PathIntegrator {
+    fn get_radiance(&self, ray: Ray, world: &dyn Hittable, lights: &HittableList) -> Color {
+        let mut ray = ray;
+        let mut radiance = Color::new(0.0, 0.0, 0.0);
+        let mut throughput = Color::new(1.0, 1.0, 1.0);
+        // NOTE: Density the last bounce picked the ray's direction with, None for camera
+        // rays and mirrors whose emission is always counted in full
+        let mut scatter_pdf: Option<f64> = None;
+
+        for depth in 0..self.max_depth {
+            let hit = match world.hit(&ray, &Interval::new(0.001, F_INF)) {
+                Some(hit) => hit,
+                None => {
+                    radiance = radiance.addv(throughput.multiply(self.background));
+                    break;
+                }
+            };
+
+            let material: Materials = hit.get_material();
+            let (u, v) = hit.get_texture_coordinates();
+            let hit_point = hit.get_point();
+
+            let emission_weight = match scatter_pdf {
+                Some(pdf) if lights.get_num_hittables() > 0 => {
+                    power_heuristic(pdf, lights.pdf_value(&ray))
+                }
+                _ => 1.0,
+            };
+            let emission_color = material.emit(u, v, hit_point).scale(emission_weight);
+            radiance = radiance.addv(throughput.multiply(emission_color));
+
+            let scatter = match material.scatter(ray, hit.clone()) {
+                Some(scatter) => scatter,
+                None => break,
+            };
+
+            match scatter {
+                ScatterRecord::Specular {
+                    ray: specular_ray,
+                    attenuation,
+                } => {
+                    throughput = throughput.multiply(attenuation);
+                    ray = specular_ray;
+                    scatter_pdf = None;
+                }
+                ScatterRecord::Sampled(pdf) => {
+                    let direct_light = self.sample_lights(ray, &hit, &pdf, world, lights);
+                    radiance = radiance.addv(throughput.multiply(direct_light));
+
+                    let Some(direction) = pdf.generate() else {
+                        break;
+                    };
+                    let pdf_value = pdf.get_value(direction);
+                    let bsdf = material.evaluate(&ray, &hit, direction);
+
+                    if pdf_value <= 0.0 || bsdf.is_zero() {
+                        break;
+                    }
+
+                    throughput = throughput.multiply(bsdf).scale(1.0 / pdf_value);
+                    ray = Ray::new(hit_point, direction, Some(ray.get_time()));
+                    scatter_pdf = Some(pdf_value);
+                }
+            }
+
+            // NOTE: Not after the last bounce, so roulette_depth >= max_depth turns it off
+            if depth + 1 >= self.roulette_depth && depth + 1 < self.max_depth {
+                match russian_roulette(throughput) {
+                    Some(survivor) => throughput = survivor,
+                    None => break,
+                }
+            }
+        }
+
+        radiance
+    }
+}
@@ -1,98 +1,278 @@
 mod bvh;
 mod camera;
+mod checkpoint;
+mod cli;
+mod integrators;
 mod materials;
 mod objects;
+mod output;
+mod pdf;
 mod ray;
+mod render_buffer;
+mod samplers;
 mod scene;
+mod scene_file;
 mod texture;
+mod tone_mapping;
 mod transformation;
 mod utils;
 mod vector;
 
-use std::sync::Arc;
-
-use camera::Camera;
-use materials::{
-    Materials, dielectric::DielectricMaterial, lambertian::LambertianMaterial, metal::MetalMaterial,
-};
-use objects::{hittable::HittableList, sphere::Sphere};
-use rand::random;
-use vector::{Point, Vector};
+use std::process;
 
 use crate::{
-    bvh::bvh::BvhNode,
-    scene::{
-        basic_light_scene::simple_light_scene,
-        basic_scene::basic_scene,
-        camera_fov_scene::camera_fov_scene,
-        checker_ground_scene::checker_scene,
-        cornell_box_scene::cornell_box_scene,
-        cornell_smoke_scene::cornell_smoke_scene,
-        earth_scene::earth_scene,
-        perlin_earth_scene::perlin_earth_scene,
-        perlin_scene::perlin_scene,
-        quad_scene::quad_scene,
-        week_render::week_scene,
-        weekend_render::{self, weekend_scene},
-    },
-    texture::{
-        checker::CheckerTexture, image::ImageTexture, perlin_noise, solid_color::SolidColorTexture,
-        texture::Texture,
-    },
-    utils::{
-        constants::PI,
-        functions::{random_double, random_double_in_range},
+    checkpoint::{Checkpoint, CheckpointError, load_checkpoint, save_checkpoint},
+    cli::{CliCommand, DEFAULT_SAMPLES_PER_PASS, RenderOptions, USAGE, parse_args},
+    output::{OutputFormat, RenderError},
+    render_buffer::AccumulationBuffer,
+    scene::{SCENES, find_scene, scene::Scene},
+    scene_file::{
+        parser::load_scene_file,
+        writer::{save_scene_file, write_scene},
     },
-    vector::{Color, get_random_unit_vector},
+    tone_mapping::ToneMapping,
+    utils::functions::{random_u64, seed_rng},
 };
 
 fn main() {
-    // NOTE: Scene 1 - Triple Spheres of different materials + Air Bubble within Glass Sphere
-    // let basic_scene = basic_scene();
-    // basic_scene.render();
-
-    // NOTE: Scene 2 - Testing Camera FOV
-    // let camera_fov_scene = camera_fov_scene();
-    // camera_fov_scene.render()
-
-    // NOTE: Final Render Scene 1 (From Ray Tracing in a Weekend)
-    // let weekend_render = weekend_scene();
-    // weekend_render.render();
-
-    // NOTE: Final Render Scene 1 (From Ray Tracing in a Weekend)
-    // but with a Checker Texture on the ground
-    // let checker_scene = checker_scene();
-    // checker_scene.render();
-
-    // NOTE: Scene 5 - Earth
-    // let earth_scene = earth_scene();
-    // earth_scene.render();
-
-    // NOTE: Perlin Noise ground + Main centre sphere
-    // let perlin_scene = perlin_scene();
-    // perlin_scene.render();
-
-    // NOTE: Basic Quad Scene (Think a box but the sides are not connected)
-    // let quad_scene = quad_scene();
-    // quad_scene.render();
-
-    // NOTE: Perlin Noise ground + Main centre sphere of Earth Image Texture
-    // let perlin_earth = perlin_earth_scene();
-    // perlin_earth.render();
-
-    // NOTE: Simple Light Scene requiring a diffuse light
-    // let simple_light_scene = simple_light_scene();
-    // simple_light_scene.render();
-
-    // NOTE: Cornell Box Scene
-    // let cornell_box_scene = cornell_box_scene();
-    // cornell_box_scene.render();
-
-    // NOTE: Cornell Box Scene but with Smoke boxes
-    // let cornell_smoke_scene = cornell_smoke_scene();
-    // cornell_smoke_scene.render();
-
-    // NOTE: Final Render Scene (From Ray Tracing, the Next Week)
-    let week_render = week_scene();
-    week_render.render();
+    let command = match parse_args(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            process::exit(2);
+        }
+    };
+
+    match command {
+        CliCommand::Help => println!("{}", USAGE),
+        CliCommand::ListScenes => list_scenes(),
+        CliCommand::Render(options) => render(*options),
+    }
+}
+
+fn list_scenes() {
+    for entry in SCENES {
+        println!("{:<16}{}", entry.name, entry.description);
+    }
+}
+
+fn render(options: RenderOptions) {
+    // NOTE: Seeded before the scene is built so randomly generated scenes come out the
+    // same every time too. A scene file's own seed is skipped when this one is given.
+    if let Some(seed) = options.seed {
+        seed_rng(seed);
+    }
+
+    let mut scene = match &options.scene_file {
+        Some(path) => match load_scene_file(path, options.seed) {
+            Ok(scene) => scene,
+            Err(err) => {
+                eprintln!("{}: {}", path, err);
+                process::exit(1);
+            }
+        },
+        None => match find_scene(&options.scene) {
+            Some(entry) => (entry.build)(),
+            None => {
+                eprintln!(
+                    "Unknown scene '{}', use --list to see the available scenes",
+                    options.scene
+                );
+                process::exit(2);
+            }
+        },
+    };
+
+    // NOTE: Only override what was given, everything else keeps the scene's own settings
+    let mut builder = scene.get_camera().to_builder();
+
+    if let Some(aspect_ratio) = options.aspect_ratio {
+        builder = builder.aspect_ratio(aspect_ratio);
+    }
+    if let Some(image_width) = options.image_width {
+        builder = builder.image_width(image_width);
+    }
+    if let Some(samples_per_pixel) = options.samples_per_pixel {
+        builder = builder.samples_per_pixel(samples_per_pixel);
+    }
+    if let Some(max_depth) = options.max_depth {
+        builder = builder.max_depth(max_depth);
+    }
+    if let Some(roulette_depth) = options.roulette_depth {
+        builder = builder.roulette_depth(roulette_depth);
+    }
+    if options.min_samples.is_some() || options.noise_threshold.is_some() {
+        let (min_samples, noise_threshold) = scene.get_camera().get_adaptive_sampling();
+        builder = builder.adaptive_sampling(
+            options.min_samples.unwrap_or(min_samples),
+            options.noise_threshold.unwrap_or(noise_threshold),
+        );
+    }
+    if let Some(sampler) = options.sampler {
+        builder = builder.sampler(sampler);
+    }
+    if let Some(integrator) = options.integrator {
+        builder = builder.integrator(integrator);
+    }
+    if options.seed.is_some() {
+        builder = builder.seed(options.seed);
+    }
+    if let Some(defocus_angle) = options.defocus_angle {
+        builder = builder.defocus_angle(defocus_angle);
+    }
+    if let Some(focus_dist) = options.focus_dist {
+        builder = builder.focus_dist(focus_dist);
+    }
+    if options.auto_focus {
+        builder = builder.auto_focus(true);
+    }
+    if let Some(num_threads) = options.num_threads {
+        builder = builder.num_threads(num_threads);
+    }
+    if let Some(tile_size) = options.tile_size {
+        builder = builder.tile_size(tile_size);
+    }
+
+    if options.tone_map.is_some() || options.exposure.is_some() || options.transfer.is_some() {
+        let tone_mapping = builder.get_tone_mapping();
+        builder = builder.tone_mapping(ToneMapping::new(
+            options.tone_map.unwrap_or(tone_mapping.get_operator()),
+            options.exposure.unwrap_or(tone_mapping.get_exposure()),
+            options.transfer.unwrap_or(tone_mapping.get_curve()),
+        ));
+    }
+
+    let camera = match builder.build() {
+        Ok(camera) => camera,
+        Err(err) => {
+            eprintln!("Invalid camera settings: {}", err);
+            process::exit(2);
+        }
+    };
+
+    scene.set_camera(camera);
+
+    match &options.export {
+        Some(path) => match save_scene_file(&scene, path) {
+            Ok(_) => println!("successfully exported to {}", path),
+            Err(err) => {
+                eprintln!("Could not export scene to {}: {}", path, err);
+                process::exit(1);
+            }
+        },
+        None => {
+            let format = match options.format {
+                Some(format) => Ok(format),
+                None => OutputFormat::from_path(&options.output),
+            };
+
+            let result = format.and_then(|format| {
+                if options.progressive.is_some() || options.checkpoint.is_some() {
+                    render_progressive(&scene, &options, format)
+                } else {
+                    scene.render(&options.output, format)
+                }
+            });
+
+            match result {
+                Ok(_) => println!("successfully saved to {}", options.output),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    process::exit(1);
+                }
+            }
+        }
+    }
+}
+
+// NOTE: With --progressive the output is overwritten with a preview after every pass,
+// so a long render can be stopped as soon as it looks good enough. With --checkpoint
+// the totals are saved after every pass too, so an interrupted render can be resumed.
+fn render_progressive(
+    scene: &Scene,
+    options: &RenderOptions,
+    format: OutputFormat,
+) -> Result<(), RenderError> {
+    let output_path = options.output.as_str();
+    let tone_mapping = scene.get_camera().get_tone_mapping();
+    let (samples_per_pixel, _) = scene.get_camera().get_sampling_specs();
+    let samples_per_pass = options.progressive.unwrap_or(DEFAULT_SAMPLES_PER_PASS);
+    let scene_description = write_scene(scene);
+
+    let (accumulator, samples_done, render_seed) = match &options.checkpoint {
+        Some(path) if options.resume => {
+            let checkpoint = load_checkpoint(path)?;
+
+            if checkpoint.get_scene_description() != scene_description {
+                return Err(CheckpointError::Mismatch { path: path.clone() }.into());
+            }
+
+            eprintln!(
+                "Resuming from {} with {}/{} samples per pixel done",
+                path,
+                checkpoint.get_samples_done(),
+                samples_per_pixel
+            );
+
+            let samples_done = checkpoint.get_samples_done();
+            let render_seed = checkpoint.get_render_seed();
+            (checkpoint.into_accumulator(), samples_done, render_seed)
+        }
+        _ => {
+            let (image_width, image_height) = scene.get_camera().get_image_size();
+            let render_seed = scene.get_camera().get_seed().unwrap_or_else(random_u64);
+            (
+                AccumulationBuffer::new(image_width, image_height),
+                0,
+                render_seed,
+            )
+        }
+    };
+
+    let mut checkpoint_error = None;
+
+    let buffer = scene.resume_progressive(
+        accumulator,
+        samples_done,
+        samples_per_pass,
+        render_seed,
+        |accumulator, samples_done| {
+            if let Some(path) = &options.checkpoint {
+                let checkpoint = Checkpoint::new(
+                    scene_description.clone(),
+                    render_seed,
+                    samples_done,
+                    accumulator.clone(),
+                );
+
+                if let Err(err) = save_checkpoint(&checkpoint, path) {
+                    eprintln!("{}", err);
+                    checkpoint_error.get_or_insert(err);
+                }
+            }
+
+            // NOTE: The last pass is saved as the final image below
+            if options.progressive.is_none() || samples_done == samples_per_pixel {
+                return;
+            }
+
+            match accumulator
+                .to_render_buffer()
+                .save(output_path, format, &tone_mapping)
+            {
+                Ok(_) => eprintln!(
+                    "Saved preview with {}/{} samples per pixel to {}",
+                    samples_done, samples_per_pixel, output_path
+                ),
+                Err(err) => eprintln!("Could not save preview: {}", err),
+            }
+        },
+    );
+
+    buffer.save(output_path, format, &tone_mapping)?;
+
+    match checkpoint_error {
+        Some(err) => Err(err.into()),
+        None => Ok(()),
+    }
 }
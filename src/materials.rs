@@ -4,6 +4,8 @@ pub mod emission;
 pub mod isotropic;
 pub mod lambertian;
 pub mod metal;
+pub mod microfacet;
+pub mod principled;
 pub mod scatterable;
 
 use crate::{
@@ -12,12 +14,14 @@ use crate::{
     },
     objects::hittable::HitRecord,
     ray::Ray,
-    vector::{Color, Point},
+    scene_file::writer::SceneWriter,
+    vector::{Color, Point, Vector},
 };
 
 use dielectric::DielectricMaterial;
 use lambertian::LambertianMaterial;
 use metal::MetalMaterial;
+use principled::PrincipledMaterial;
 use scatterable::{ScatterRecord, Scatterable};
 
 #[derive(Clone)]
@@ -27,6 +31,25 @@ pub enum Materials {
     Dielectric(DielectricMaterial),
     Isotropic(IsotropicMaterial),
     Diffuse(DiffuseLightMaterial),
+    Principled(PrincipledMaterial),
+}
+
+impl Materials {
+    pub fn is_emissive(&self) -> bool {
+        matches!(self, Materials::Diffuse(_))
+    }
+
+    // NOTE: Type and parameters as written after the name in a scene file material statement
+    pub fn to_scene_definition(&self, writer: &mut SceneWriter) -> String {
+        match self {
+            Materials::Lambertian(mat) => mat.to_scene_definition(writer),
+            Materials::Metal(mat) => mat.to_scene_definition(writer),
+            Materials::Dielectric(mat) => mat.to_scene_definition(writer),
+            Materials::Isotropic(mat) => mat.to_scene_definition(writer),
+            Materials::Diffuse(mat) => mat.to_scene_definition(writer),
+            Materials::Principled(mat) => mat.to_scene_definition(writer),
+        }
+    }
 }
 
 impl Scatterable for Materials {
@@ -36,9 +59,21 @@ impl Scatterable for Materials {
             Materials::Metal(mat) => mat.scatter(ray, hit_record),
             Materials::Dielectric(mat) => mat.scatter(ray, hit_record),
             Materials::Isotropic(mat) => mat.scatter(ray, hit_record),
+            Materials::Principled(mat) => mat.scatter(ray, hit_record),
             _ => None,
         }
     }
+
+    fn evaluate(&self, ray: &Ray, hit_record: &HitRecord, direction: Vector) -> Color {
+        match self {
+            Materials::Lambertian(mat) => mat.evaluate(ray, hit_record, direction),
+            Materials::Metal(mat) => mat.evaluate(ray, hit_record, direction),
+            Materials::Dielectric(mat) => mat.evaluate(ray, hit_record, direction),
+            Materials::Isotropic(mat) => mat.evaluate(ray, hit_record, direction),
+            Materials::Principled(mat) => mat.evaluate(ray, hit_record, direction),
+            _ => Color::new(0.0, 0.0, 0.0),
+        }
+    }
 }
 
 impl Emission for Materials {
@@ -1,57 +1,285 @@
+use std::sync::Arc;
+
 use crate::{
+    materials::{
+        microfacet::{
+            fresnel_dielectric, is_effectively_smooth, rough_dielectric_f, rough_dielectric_pdf,
+            roughness_to_alpha, sample_rough_dielectric,
+        },
+        scatterable::{ScatterRecord, Scatterable},
+    },
     objects::hittable::HitRecord,
+    pdf::{Pdfs, pdf::Pdf},
     ray::Ray,
-    utils::functions::random_double,
+    scene_file::writer::{SceneWriter, format_vector},
+    texture::{solid_color::SolidColorTexture, texture::Texture},
+    utils::{functions::random_double, onb::Onb},
     vector::{Color, Vector, dot_product, reflect, refract},
 };
 
-use super::scatterable::{ScatterRecord, Scatterable};
-
-#[derive(Clone, Copy)]
+// NOTE: Glass and the like. Smooth by default, a roughness (read from the texture's
+// luminance) frosts it with GGX microfacets, and an absorption colour tints the light
+// travelling through the inside.
+#[derive(Clone)]
 pub struct DielectricMaterial {
     refraction_index: f64,
+    roughness: Arc<dyn Texture>,
+    // NOTE: The colour white light has turned after travelling the distance through it
+    absorption: Option<(Color, f64)>,
 }
 
 impl DielectricMaterial {
     pub fn new(refraction_index: f64) -> DielectricMaterial {
-        DielectricMaterial { refraction_index }
+        DielectricMaterial {
+            refraction_index,
+            roughness: Arc::new(SolidColorTexture::new_from_rgb(0.0, 0.0, 0.0)),
+            absorption: None,
+        }
+    }
+
+    pub fn roughness(mut self, roughness: Arc<dyn Texture>) -> DielectricMaterial {
+        self.roughness = roughness;
+        self
+    }
+
+    pub fn absorption(mut self, color: Color, distance: f64) -> DielectricMaterial {
+        self.absorption = Some((color, distance));
+        self
+    }
+
+    pub fn to_scene_definition(&self, writer: &mut SceneWriter) -> String {
+        let mut definition = format!(
+            "dielectric refraction_index={} roughness={}",
+            self.refraction_index,
+            writer.add_number_or_texture(&self.roughness)
+        );
+
+        if let Some((color, distance)) = self.absorption {
+            definition += &format!(
+                " absorption={} absorption_distance={}",
+                format_vector(color),
+                distance
+            );
+        }
+
+        definition
+    }
+
+    fn get_alpha(&self, hit_record: &HitRecord) -> f64 {
+        let (u, v) = hit_record.get_texture_coordinates();
+        let roughness = self
+            .roughness
+            .get_value(u, v, hit_record.get_point())
+            .luminance()
+            .clamp(0.0, 1.0);
+
+        roughness_to_alpha(roughness)
+    }
+
+    // NOTE: Beer-Lambert law, a ray hitting the back of the surface has come through
+    // the inside all the way from its origin
+    fn get_transmittance(&self, ray: &Ray, hit_record: &HitRecord) -> Color {
+        match self.absorption {
+            Some((color, distance)) if !hit_record.get_front() => {
+                let travelled = hit_record.get_t() * ray.get_direction().get_length();
+                let channel = |c: f64| c.clamp(0.0, 1.0).powf(travelled / distance);
+                let (r, g, b) = color.get_point();
+
+                Color::new(channel(r), channel(g), channel(b))
+            }
+            _ => Color::new(1.0, 1.0, 1.0),
+        }
     }
 
-    // NOTE: Schlick Approximation
-    pub fn reflectance(cosine: f64, refraction_index: f64) -> f64 {
-        let r0 = (1.0 - refraction_index) / (1.0 + refraction_index);
-        let r0_squared = r0 * r0;
+    fn get_bsdf(&self, ray: &Ray, hit_record: &HitRecord, alpha: f64) -> DielectricBsdf {
+        let basis = Onb::new(hit_record.get_normal());
 
-        r0_squared + (1.0 - r0_squared) * f64::powf(1.0 - cosine, 5.0)
+        DielectricBsdf {
+            basis,
+            wo: basis.inverse_transform(ray.get_direction().unit().negate()),
+            alpha,
+            eta: self.get_eta(hit_record),
+            transmittance: self.get_transmittance(ray, hit_record),
+        }
+    }
+
+    // NOTE: The normal always faces the incoming ray, so going in or coming out only
+    // changes which way the refraction index is taken
+    fn get_eta(&self, hit_record: &HitRecord) -> f64 {
+        if hit_record.get_front() {
+            self.refraction_index
+        } else {
+            1.0 / self.refraction_index
+        }
     }
 }
 
 impl Scatterable for DielectricMaterial {
     fn scatter(&self, ray: Ray, hit_record: HitRecord) -> Option<ScatterRecord> {
-        let normal = hit_record.get_normal();
+        let alpha = self.get_alpha(&hit_record);
 
-        let ri: f64 = if hit_record.get_front() {
-            1.0 / self.refraction_index
-        } else {
-            self.refraction_index
-        };
+        if !is_effectively_smooth(alpha) {
+            return Some(ScatterRecord::Sampled(Pdfs::Dielectric(self.get_bsdf(
+                &ray,
+                &hit_record,
+                alpha,
+            ))));
+        }
 
+        let normal = hit_record.get_normal();
+        let eta = self.get_eta(&hit_record);
         let unit_direction = ray.get_direction().unit();
 
         let cos_theta = f64::min(dot_product(unit_direction.negate(), normal), 1.0);
-        let sin_theta = (1.0 - (cos_theta * cos_theta)).sqrt();
-
-        let cannot_refract = ri * sin_theta > 1.0;
-        let reflect_angle: f64 = DielectricMaterial::reflectance(cos_theta, ri);
 
-        let ray_direction: Vector = if cannot_refract || reflect_angle > random_double() {
+        // NOTE: Always 1 past the critical angle, where nothing can refract
+        let ray_direction = if fresnel_dielectric(cos_theta, eta) > random_double() {
             reflect(unit_direction, normal)
         } else {
-            refract(unit_direction, normal, ri)
+            refract(unit_direction, normal, 1.0 / eta)
         };
 
         let scattered_ray = Ray::new(hit_record.get_point(), ray_direction, Some(ray.get_time()));
 
-        Some(ScatterRecord::new(scattered_ray, Color::new(1.0, 1.0, 1.0)))
+        Some(ScatterRecord::Specular {
+            ray: scattered_ray,
+            attenuation: self.get_transmittance(&ray, &hit_record),
+        })
+    }
+
+    fn evaluate(&self, ray: &Ray, hit_record: &HitRecord, direction: Vector) -> Color {
+        let alpha = self.get_alpha(hit_record);
+
+        if is_effectively_smooth(alpha) {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+
+        self.get_bsdf(ray, hit_record, alpha).evaluate(direction)
+    }
+}
+
+// NOTE: The rough dielectric at one hit, both evaluates the BSDF and samples it
+#[derive(Clone, Copy)]
+pub struct DielectricBsdf {
+    basis: Onb,
+    // NOTE: Towards the viewer, in the local frame of basis
+    wo: Vector,
+    alpha: f64,
+    eta: f64,
+    transmittance: Color,
+}
+
+impl DielectricBsdf {
+    pub fn evaluate(&self, direction: Vector) -> Color {
+        let (wo, wi) = (self.wo, self.basis.inverse_transform(direction.unit()));
+        let (cos_o, cos_i) = (wo.get_point().2, wi.get_point().2);
+
+        if cos_o <= 0.0 {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+
+        let f = rough_dielectric_f(wo, wi, self.alpha, self.eta);
+
+        self.transmittance.scale(f * cos_i.abs())
+    }
+}
+
+impl Pdf for DielectricBsdf {
+    fn get_value(&self, direction: Vector) -> f64 {
+        let (wo, wi) = (self.wo, self.basis.inverse_transform(direction.unit()));
+
+        if wo.get_point().2 <= 0.0 {
+            return 0.0;
+        }
+
+        rough_dielectric_pdf(wo, wi, self.alpha, self.eta)
+    }
+
+    fn generate(&self) -> Option<Vector> {
+        if self.wo.get_point().2 <= 0.0 {
+            return None;
+        }
+
+        let u = (random_double(), random_double());
+        let wi = sample_rough_dielectric(self.wo, self.alpha, self.eta, u, random_double())?;
+
+        Some(self.basis.transform(wi))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        materials::Materials,
+        utils::{constants::PI, functions::seed_rng},
+        vector::{Point, get_random_unit_vector},
+    };
+
+    fn hit(ray: &Ray, t: f64, outward_normal: Vector, material: &DielectricMaterial) -> HitRecord {
+        HitRecord::new(
+            ray.at(t),
+            outward_normal,
+            t,
+            ray,
+            Materials::Dielectric(material.clone()),
+            0.0,
+            0.0,
+        )
+    }
+
+    #[test]
+    fn absorbs_light_travelling_through_the_inside() {
+        let material = DielectricMaterial::new(1.5).absorption(Color::new(0.5, 0.8, 1.0), 2.0);
+        let up = Vector::new(0.0, 1.0, 0.0);
+
+        // NOTE: Leaving after 4 units inside is twice the absorption distance
+        let inside = Ray::new(Point::new(0.0, 0.0, 0.0), up.scale(2.0), None);
+        let (r, g, b) = material
+            .get_transmittance(&inside, &hit(&inside, 2.0, up, &material))
+            .get_point();
+        assert!((r - 0.25).abs() < 1e-12 && (g - 0.64).abs() < 1e-12 && b == 1.0);
+
+        // NOTE: Nothing is lost on the way in
+        let outside = Ray::new(Point::new(0.0, -5.0, 0.0), up, None);
+        let (r, g, b) = material
+            .get_transmittance(&outside, &hit(&outside, 4.0, up.negate(), &material))
+            .get_point();
+        assert!(r == 1.0 && g == 1.0 && b == 1.0);
+    }
+
+    #[test]
+    fn rough_samples_agree_with_the_evaluated_bsdf() {
+        seed_rng(1);
+
+        let runs = 200_000;
+
+        // NOTE: Going into the glass and coming out of it
+        for eta in [1.5, 1.0 / 1.5] {
+            let bsdf = DielectricBsdf {
+                basis: Onb::new(Vector::new(0.0, 0.0, 1.0)),
+                wo: Vector::new(0.4, 0.1, 0.8).unit(),
+                alpha: 0.3,
+                eta,
+                transmittance: Color::new(1.0, 1.0, 1.0),
+            };
+
+            let (mut sampled, mut uniform) = (0.0, 0.0);
+            for _ in 0..runs {
+                if let Some(direction) = bsdf.generate() {
+                    sampled += bsdf.evaluate(direction).luminance() / bsdf.get_value(direction);
+                }
+
+                let direction = get_random_unit_vector();
+                uniform += bsdf.evaluate(direction).luminance() * 4.0 * PI;
+            }
+
+            let n = runs as f64;
+            assert!(
+                (sampled / n - uniform / n).abs() < 0.03 * uniform / n,
+                "eta {}",
+                eta
+            );
+        }
     }
 }
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use crate::{
     materials::emission::Emission,
+    scene_file::writer::SceneWriter,
     texture::texture::Texture,
     vector::{Color, Point},
 };
@@ -15,6 +16,13 @@ impl DiffuseLightMaterial {
     pub fn new(texture: Arc<dyn Texture>) -> DiffuseLightMaterial {
         DiffuseLightMaterial { texture }
     }
+
+    pub fn to_scene_definition(&self, writer: &mut SceneWriter) -> String {
+        format!(
+            "diffuse_light texture={}",
+            writer.add_texture(&self.texture)
+        )
+    }
 }
 
 impl Emission for DiffuseLightMaterial {
@@ -3,9 +3,12 @@ use std::sync::Arc;
 use crate::{
     materials::scatterable::{ScatterRecord, Scatterable},
     objects::hittable::HitRecord,
+    pdf::{Pdfs, sphere::SpherePdf},
     ray::Ray,
+    scene_file::writer::SceneWriter,
     texture::texture::Texture,
-    vector::get_random_unit_vector,
+    utils::constants::PI,
+    vector::{Color, Vector},
 };
 
 #[derive(Clone)]
@@ -17,18 +20,23 @@ impl IsotropicMaterial {
     pub fn new(texture: Arc<dyn Texture>) -> IsotropicMaterial {
         IsotropicMaterial { texture }
     }
+
+    pub fn to_scene_definition(&self, writer: &mut SceneWriter) -> String {
+        format!("isotropic texture={}", writer.add_texture(&self.texture))
+    }
 }
 
 impl Scatterable for IsotropicMaterial {
-    fn scatter(&self, ray: Ray, hit_record: HitRecord) -> Option<ScatterRecord> {
+    fn scatter(&self, _ray: Ray, _hit_record: HitRecord) -> Option<ScatterRecord> {
+        Some(ScatterRecord::Sampled(Pdfs::Sphere(SpherePdf)))
+    }
+
+    // NOTE: Every direction is equally likely
+    fn evaluate(&self, _ray: &Ray, hit_record: &HitRecord, _direction: Vector) -> Color {
         let (u, v) = hit_record.get_texture_coordinates();
-        let scatter_ray = Ray::new(
-            hit_record.get_point(),
-            get_random_unit_vector(),
-            Some(ray.get_time()),
-        );
-        let attenuation = self.texture.get_value(u, v, hit_record.get_point());
 
-        Some(ScatterRecord::new(scatter_ray, attenuation))
+        self.texture
+            .get_value(u, v, hit_record.get_point())
+            .scale(1.0 / (4.0 * PI))
     }
 }
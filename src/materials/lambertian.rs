@@ -1,8 +1,11 @@
 use crate::{
     objects::hittable::HitRecord,
+    pdf::{Pdfs, cosine::CosinePdf},
     ray::Ray,
-    texture::{solid_color::SolidColorTexture, texture::Texture},
-    vector::{Color, Vector, get_random_unit_vector},
+    scene_file::writer::SceneWriter,
+    texture::texture::Texture,
+    utils::constants::PI,
+    vector::{Color, Vector, dot_product},
 };
 use std::{fmt::Display, sync::Arc};
 
@@ -17,28 +20,27 @@ impl LambertianMaterial {
     pub fn new(texture: Arc<dyn Texture>) -> LambertianMaterial {
         LambertianMaterial { texture }
     }
+
+    pub fn to_scene_definition(&self, writer: &mut SceneWriter) -> String {
+        format!("lambertian texture={}", writer.add_texture(&self.texture))
+    }
 }
 
 impl Scatterable for LambertianMaterial {
-    fn scatter(&self, ray: Ray, hit_record: HitRecord) -> Option<ScatterRecord> {
-        let surface_normal_vec = hit_record.get_normal();
-        let mut scatter_ray_direction: Vector = get_random_unit_vector().addv(surface_normal_vec);
-
-        if scatter_ray_direction.near_zero() {
-            scatter_ray_direction = surface_normal_vec;
-        }
-
-        let scatter_ray = Ray::new(
-            hit_record.get_point(),
-            scatter_ray_direction,
-            Some(ray.get_time()),
-        );
+    fn scatter(&self, _ray: Ray, hit_record: HitRecord) -> Option<ScatterRecord> {
+        Some(ScatterRecord::Sampled(Pdfs::Cosine(CosinePdf::new(
+            hit_record.get_normal(),
+        ))))
+    }
 
+    // NOTE: albedo / pi times the cosine, which the CosinePdf it scatters with follows
+    fn evaluate(&self, _ray: &Ray, hit_record: &HitRecord, direction: Vector) -> Color {
+        let cosine = dot_product(hit_record.get_normal(), direction.unit());
         let (u, v) = hit_record.get_texture_coordinates();
-        Some(ScatterRecord::new(
-            scatter_ray,
-            self.texture.get_value(u, v, hit_record.get_point()),
-        ))
+
+        self.texture
+            .get_value(u, v, hit_record.get_point())
+            .scale(f64::max(cosine, 0.0) / PI)
     }
 }
 
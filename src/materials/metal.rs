@@ -1,37 +1,322 @@
+use std::sync::Arc;
+
 use crate::{
+    materials::{
+        microfacet::{
+            fresnel_conductor, ggx_d, ggx_g, ggx_vndf_pdf, is_effectively_smooth, reflect_about,
+            roughness_to_alpha, sample_ggx_vndf, schlick_fresnel,
+        },
+        scatterable::{ScatterRecord, Scatterable},
+    },
     objects::hittable::HitRecord,
+    pdf::{Pdfs, pdf::Pdf},
     ray::Ray,
-    vector::{Color, get_random_unit_vector, reflect},
+    scene_file::writer::{SceneWriter, format_vector},
+    texture::{solid_color::SolidColorTexture, texture::Texture},
+    utils::{functions::random_double, onb::Onb},
+    vector::{Color, Vector, dot_product, reflect},
 };
 
-use super::scatterable::{ScatterRecord, Scatterable};
+// NOTE: Measured complex refraction indices (eta + ik) at roughly the red, green and
+// blue wavelengths
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Conductor {
+    Gold,
+    Copper,
+    Aluminium,
+}
 
+impl Conductor {
+    pub fn from_name(name: &str) -> Option<Conductor> {
+        match name.to_ascii_lowercase().as_str() {
+            "gold" => Some(Conductor::Gold),
+            "copper" => Some(Conductor::Copper),
+            "aluminium" | "aluminum" => Some(Conductor::Aluminium),
+            _ => None,
+        }
+    }
+
+    // NOTE: Returns (eta, k)
+    pub fn get_complex_ior(&self) -> (Color, Color) {
+        match self {
+            Conductor::Gold => (
+                Color::new(0.143, 0.375, 1.442),
+                Color::new(3.983, 2.386, 1.603),
+            ),
+            Conductor::Copper => (
+                Color::new(0.200, 0.924, 1.102),
+                Color::new(3.913, 2.453, 2.142),
+            ),
+            Conductor::Aluminium => (
+                Color::new(1.657, 0.880, 0.521),
+                Color::new(9.224, 6.270, 4.837),
+            ),
+        }
+    }
+}
+
+// NOTE: Either an artist friendly colour head on (Schlick's approximation takes it to
+// white at grazing angles) or the exact Fresnel equations for a complex refraction index
 #[derive(Clone, Copy)]
+enum Reflectance {
+    Albedo(Color),
+    ComplexIor { eta: Color, k: Color },
+}
+
+impl Reflectance {
+    fn get_fresnel(&self, cosine: f64) -> Color {
+        match self {
+            Reflectance::Albedo(albedo) => schlick_fresnel(*albedo, cosine),
+            Reflectance::ComplexIor { eta, k } => fresnel_conductor(cosine, *eta, *k),
+        }
+    }
+}
+
+// NOTE: A rough conductor, GGX microfacets that each reflect like a perfect mirror.
+// Roughness reads the texture's luminance, 0 is polished and 1 is brushed to a blur.
+#[derive(Clone)]
 pub struct MetalMaterial {
-    albedo: Color,
-    fuzz: f64,
+    reflectance: Reflectance,
+    roughness: Arc<dyn Texture>,
 }
 
 impl MetalMaterial {
-    pub fn new(albedo: Color, fuzz: f64) -> MetalMaterial {
-        MetalMaterial { albedo, fuzz }
+    pub fn new(albedo: Color, roughness: f64) -> MetalMaterial {
+        MetalMaterial {
+            reflectance: Reflectance::Albedo(albedo),
+            roughness: Arc::new(SolidColorTexture::new_from_rgb(
+                roughness, roughness, roughness,
+            )),
+        }
+    }
+
+    pub fn new_from_ior(eta: Color, k: Color, roughness: f64) -> MetalMaterial {
+        MetalMaterial {
+            reflectance: Reflectance::ComplexIor { eta, k },
+            ..MetalMaterial::new(Color::new(1.0, 1.0, 1.0), roughness)
+        }
+    }
+
+    pub fn new_from_conductor(conductor: Conductor, roughness: f64) -> MetalMaterial {
+        let (eta, k) = conductor.get_complex_ior();
+
+        MetalMaterial::new_from_ior(eta, k, roughness)
+    }
+
+    pub fn roughness(mut self, roughness: Arc<dyn Texture>) -> MetalMaterial {
+        self.roughness = roughness;
+        self
+    }
+
+    pub fn to_scene_definition(&self, writer: &mut SceneWriter) -> String {
+        let reflectance = match self.reflectance {
+            Reflectance::Albedo(albedo) => format!("albedo={}", format_vector(albedo)),
+            Reflectance::ComplexIor { eta, k } => {
+                format!("eta={} k={}", format_vector(eta), format_vector(k))
+            }
+        };
+
+        format!(
+            "metal {} roughness={}",
+            reflectance,
+            writer.add_number_or_texture(&self.roughness)
+        )
+    }
+
+    fn get_alpha(&self, hit_record: &HitRecord) -> f64 {
+        let (u, v) = hit_record.get_texture_coordinates();
+        let roughness = self
+            .roughness
+            .get_value(u, v, hit_record.get_point())
+            .luminance()
+            .clamp(0.0, 1.0);
+
+        roughness_to_alpha(roughness)
+    }
+
+    fn get_bsdf(&self, ray: &Ray, hit_record: &HitRecord, alpha: f64) -> ConductorBsdf {
+        let basis = Onb::new(hit_record.get_normal());
+
+        ConductorBsdf {
+            basis,
+            wo: basis.inverse_transform(ray.get_direction().unit().negate()),
+            reflectance: self.reflectance,
+            alpha,
+        }
     }
 }
 
 impl Scatterable for MetalMaterial {
     fn scatter(&self, ray: Ray, hit_record: HitRecord) -> Option<ScatterRecord> {
-        let surface_normal_vec = hit_record.get_normal();
-        let reflected_ray_direction = reflect(ray.get_direction(), surface_normal_vec);
+        let alpha = self.get_alpha(&hit_record);
+
+        if !is_effectively_smooth(alpha) {
+            return Some(ScatterRecord::Sampled(Pdfs::Conductor(self.get_bsdf(
+                &ray,
+                &hit_record,
+                alpha,
+            ))));
+        }
+
+        let normal = hit_record.get_normal();
+        let unit_direction = ray.get_direction().unit();
+        let cosine = dot_product(unit_direction.negate(), normal);
+
+        Some(ScatterRecord::Specular {
+            ray: Ray::new(
+                hit_record.get_point(),
+                reflect(unit_direction, normal),
+                Some(ray.get_time()),
+            ),
+            attenuation: self.reflectance.get_fresnel(cosine),
+        })
+    }
+
+    fn evaluate(&self, ray: &Ray, hit_record: &HitRecord, direction: Vector) -> Color {
+        let alpha = self.get_alpha(hit_record);
+
+        if is_effectively_smooth(alpha) {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+
+        self.get_bsdf(ray, hit_record, alpha).evaluate(direction)
+    }
+}
+
+// NOTE: The conductor at one hit, both evaluates the BSDF and samples the visible normals
+#[derive(Clone, Copy)]
+pub struct ConductorBsdf {
+    basis: Onb,
+    // NOTE: Towards the viewer, in the local frame of basis
+    wo: Vector,
+    reflectance: Reflectance,
+    alpha: f64,
+}
+
+impl ConductorBsdf {
+    pub fn evaluate(&self, direction: Vector) -> Color {
+        let (wo, wi) = (self.wo, self.basis.inverse_transform(direction.unit()));
+        let (cos_o, cos_i) = (wo.get_point().2, wi.get_point().2);
+
+        if cos_o <= 0.0 || cos_i <= 0.0 {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+
+        let h = wo.addv(wi).unit();
+        let d_g = ggx_d(h, self.alpha) * ggx_g(wo, wi, self.alpha);
+
+        // NOTE: F D G / (4 cos_o cos_i), times cos_i
+        self.reflectance
+            .get_fresnel(dot_product(wo, h))
+            .scale(d_g / (4.0 * cos_o))
+    }
+}
+
+impl Pdf for ConductorBsdf {
+    fn get_value(&self, direction: Vector) -> f64 {
+        let (wo, wi) = (self.wo, self.basis.inverse_transform(direction.unit()));
+
+        if wo.get_point().2 <= 0.0 || wi.get_point().2 <= 0.0 {
+            return 0.0;
+        }
+
+        let h = wo.addv(wi).unit();
+        let wo_h = dot_product(wo, h);
+
+        if wo_h <= 0.0 {
+            return 0.0;
+        }
+
+        ggx_vndf_pdf(wo, h, self.alpha) / (4.0 * wo_h)
+    }
+
+    fn generate(&self) -> Option<Vector> {
+        if self.wo.get_point().2 <= 0.0 {
+            return None;
+        }
+
+        let h = sample_ggx_vndf(self.wo, self.alpha, (random_double(), random_double()));
+        let wi = reflect_about(self.wo, h);
+
+        // NOTE: Reflections off a microfacet can still end up below the surface
+        if wi.get_point().2 <= 0.0 {
+            return None;
+        }
+
+        Some(self.basis.transform(wi))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        utils::{constants::PI, functions::seed_rng},
+        vector::get_random_unit_vector,
+    };
+
+    fn bsdf(wo: Vector, reflectance: Reflectance, alpha: f64) -> ConductorBsdf {
+        ConductorBsdf {
+            basis: Onb::new(Vector::new(0.0, 0.0, 1.0)),
+            wo: wo.unit(),
+            reflectance,
+            alpha,
+        }
+    }
+
+    #[test]
+    fn presets_look_like_their_metals() {
+        let head_on = |conductor: Conductor| {
+            let (eta, k) = conductor.get_complex_ior();
+            fresnel_conductor(1.0, eta, k).get_point()
+        };
+
+        // NOTE: Gold and copper lose the blue, aluminium is bright and almost grey
+        let (r, g, b) = head_on(Conductor::Gold);
+        assert!(r > 0.9 && g > b && b < 0.5);
+
+        let (r, g, b) = head_on(Conductor::Copper);
+        assert!(r > 0.9 && r > g && g > b);
+
+        let (r, g, b) = head_on(Conductor::Aluminium);
+        assert!(r > 0.85 && g > 0.85 && b > 0.85);
+
+        assert_eq!(Conductor::from_name("gold"), Some(Conductor::Gold));
+        assert_eq!(Conductor::from_name("Copper"), Some(Conductor::Copper));
+        assert_eq!(Conductor::from_name("aluminum"), Some(Conductor::Aluminium));
+    }
+
+    #[test]
+    fn samples_agree_with_the_evaluated_bsdf() {
+        seed_rng(1);
+
+        let white = Reflectance::Albedo(Color::new(1.0, 1.0, 1.0));
+        let runs = 200_000;
+
+        for alpha in [0.15, 0.4, 0.8] {
+            let bsdf = bsdf(Vector::new(0.5, -0.3, 0.6), white, alpha);
 
-        let fuzz_vec = get_random_unit_vector().scale(self.fuzz);
-        let fuzzed_ray_direction = reflected_ray_direction.unit().addv(fuzz_vec);
+            // NOTE: Sampling and integrating over the whole sphere estimate the same
+            // albedo, and the density integrates to what sampling doesn't lose
+            let (mut sampled, mut kept) = (0.0, 0.0);
+            for _ in 0..runs {
+                if let Some(direction) = bsdf.generate() {
+                    sampled += bsdf.evaluate(direction).luminance() / bsdf.get_value(direction);
+                    kept += 1.0;
+                }
+            }
 
-        let scatter_ray = Ray::new(
-            hit_record.get_point(),
-            fuzzed_ray_direction,
-            Some(ray.get_time()),
-        );
+            let (mut uniform, mut pdf_total) = (0.0, 0.0);
+            for _ in 0..runs {
+                let direction = get_random_unit_vector();
+                uniform += bsdf.evaluate(direction).luminance() * 4.0 * PI;
+                pdf_total += bsdf.get_value(direction) * 4.0 * PI;
+            }
 
-        Some(ScatterRecord::new(scatter_ray, self.albedo))
+            let n = runs as f64;
+            assert!(sampled / n <= 1.0);
+            assert!((sampled / n - uniform / n).abs() < 0.03, "alpha {}", alpha);
+            assert!((kept / n - pdf_total / n).abs() < 0.03, "alpha {}", alpha);
+        }
     }
 }
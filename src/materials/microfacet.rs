@@ -0,0 +1,392 @@
+// NOTE: Microfacet helpers shared by the rough materials. Everything works on unit
+// vectors in a local frame around the shading normal (an Onb), so the normal is +z
+// and the direction towards the viewer (wo) always has z > 0.
+
+use crate::{
+    utils::constants::PI,
+    vector::{Color, Vector, cross_product, dot_product},
+};
+
+const MIN_ALPHA: f64 = 1e-3;
+
+// NOTE: Roughness is squared to get the GGX alpha so it feels linear, and kept off zero
+// so perfectly smooth surfaces still have a (very narrow) lobe to sample
+pub fn roughness_to_alpha(roughness: f64) -> f64 {
+    f64::max(roughness * roughness, MIN_ALPHA)
+}
+
+// NOTE: Lobes this narrow are better treated as a perfect mirror, sampling and
+// evaluating them separately barely ever agrees
+pub fn is_effectively_smooth(alpha: f64) -> bool {
+    alpha <= MIN_ALPHA
+}
+
+// NOTE: GGX (Trowbridge-Reitz) distribution of microfacet normals
+pub fn ggx_d(h: Vector, alpha: f64) -> f64 {
+    let (x, y, z) = h.get_point();
+
+    if z <= 0.0 {
+        return 0.0;
+    }
+
+    let alpha_squared = alpha * alpha;
+    let t = (x * x + y * y) / alpha_squared + z * z;
+
+    1.0 / (PI * alpha_squared * t * t)
+}
+
+// NOTE: Smith's auxiliary function for GGX, how much of the surface hides behind
+// microfacets when seen from w
+fn ggx_lambda(w: Vector, alpha: f64) -> f64 {
+    let (x, y, z) = w.get_point();
+
+    if z == 0.0 {
+        return f64::INFINITY;
+    }
+
+    let tan_squared = (x * x + y * y) / (z * z);
+
+    ((1.0 + alpha * alpha * tan_squared).sqrt() - 1.0) / 2.0
+}
+
+pub fn ggx_g1(w: Vector, alpha: f64) -> f64 {
+    1.0 / (1.0 + ggx_lambda(w, alpha))
+}
+
+// NOTE: Height-correlated masking and shadowing, works for both sides of the surface
+pub fn ggx_g(wo: Vector, wi: Vector, alpha: f64) -> f64 {
+    1.0 / (1.0 + ggx_lambda(wo, alpha) + ggx_lambda(wi, alpha))
+}
+
+// NOTE: Heitz's sampling of the normals visible from wo, which wastes far fewer
+// samples on microfacets facing away than sampling ggx_d itself
+pub fn sample_ggx_vndf(wo: Vector, alpha: f64, (u1, u2): (f64, f64)) -> Vector {
+    let (x, y, z) = wo.get_point();
+    let stretched = Vector::new(alpha * x, alpha * y, z).unit();
+    let (sx, sy, sz) = stretched.get_point();
+
+    let length_squared = sx * sx + sy * sy;
+    let t1 = if length_squared > 0.0 {
+        Vector::new(-sy, sx, 0.0).scale(1.0 / length_squared.sqrt())
+    } else {
+        Vector::new(1.0, 0.0, 0.0)
+    };
+    let t2 = cross_product(stretched, t1);
+
+    let r = u1.sqrt();
+    let phi = 2.0 * PI * u2;
+    let p1 = r * phi.cos();
+    let s = 0.5 * (1.0 + sz);
+    let p2 = (1.0 - s) * (1.0 - p1 * p1).sqrt() + s * r * phi.sin();
+    let p3 = f64::max(1.0 - p1 * p1 - p2 * p2, 0.0).sqrt();
+
+    let (nx, ny, nz) = t1
+        .scale(p1)
+        .addv(t2.scale(p2))
+        .addv(stretched.scale(p3))
+        .get_point();
+
+    Vector::new(alpha * nx, alpha * ny, f64::max(nz, 0.0)).unit()
+}
+
+// NOTE: Density of sample_ggx_vndf picking h
+pub fn ggx_vndf_pdf(wo: Vector, h: Vector, alpha: f64) -> f64 {
+    let (_, _, z) = wo.get_point();
+
+    ggx_g1(wo, alpha) * f64::max(dot_product(wo, h), 0.0) * ggx_d(h, alpha) / z
+}
+
+// NOTE: Burley's GTR1 distribution for the clear coat, with a longer tail than GGX
+pub fn gtr1_d(h: Vector, alpha: f64) -> f64 {
+    let (_, _, z) = h.get_point();
+
+    if z <= 0.0 {
+        return 0.0;
+    }
+
+    let alpha_squared = alpha * alpha;
+
+    (alpha_squared - 1.0) / (PI * alpha_squared.ln() * (1.0 + (alpha_squared - 1.0) * z * z))
+}
+
+// NOTE: Picks h with density gtr1_d(h) * h.z
+pub fn sample_gtr1(alpha: f64, (u1, u2): (f64, f64)) -> Vector {
+    let alpha_squared = alpha * alpha;
+    let cos_squared = (1.0 - alpha_squared.powf(1.0 - u1)) / (1.0 - alpha_squared);
+    let cos_theta = cos_squared.clamp(0.0, 1.0).sqrt();
+    let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+    let phi = 2.0 * PI * u2;
+
+    Vector::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta)
+}
+
+pub fn schlick_weight(cosine: f64) -> f64 {
+    let m = (1.0 - cosine).clamp(0.0, 1.0);
+
+    m * m * m * m * m
+}
+
+pub fn schlick_fresnel(f0: Color, cosine: f64) -> Color {
+    let white = Color::new(1.0, 1.0, 1.0);
+
+    f0.addv(white.subv(f0).scale(schlick_weight(cosine)))
+}
+
+// NOTE: Exact Fresnel reflectance of unpolarised light for a dielectric, eta is the
+// refraction index on the far side over the one on the side of the cosine
+pub fn fresnel_dielectric(cos_i: f64, eta: f64) -> f64 {
+    let (cos_i, eta) = if cos_i < 0.0 {
+        (-cos_i, 1.0 / eta)
+    } else {
+        (cos_i, eta)
+    };
+    let cos_i = cos_i.min(1.0);
+
+    let sin_t_squared = (1.0 - cos_i * cos_i) / (eta * eta);
+    if sin_t_squared >= 1.0 {
+        return 1.0;
+    }
+
+    let cos_t = (1.0 - sin_t_squared).sqrt();
+    let parallel = (eta * cos_i - cos_t) / (eta * cos_i + cos_t);
+    let perpendicular = (cos_i - eta * cos_t) / (cos_i + eta * cos_t);
+
+    (parallel * parallel + perpendicular * perpendicular) / 2.0
+}
+
+// NOTE: Exact Fresnel reflectance of a conductor with complex refraction index eta + ik,
+// one channel at a time since both vary a lot over the spectrum
+pub fn fresnel_conductor(cos_i: f64, eta: Color, k: Color) -> Color {
+    let cos_i = cos_i.clamp(0.0, 1.0);
+    let reflectance = |eta: f64, k: f64| {
+        let cos_squared = cos_i * cos_i;
+        let sin_squared = 1.0 - cos_squared;
+
+        let t0 = eta * eta - k * k - sin_squared;
+        let a_squared_plus_b_squared = (t0 * t0 + 4.0 * eta * eta * k * k).sqrt();
+        let a = f64::max(0.5 * (a_squared_plus_b_squared + t0), 0.0).sqrt();
+
+        let t1 = a_squared_plus_b_squared + cos_squared;
+        let t2 = 2.0 * cos_i * a;
+        let perpendicular = (t1 - t2) / (t1 + t2);
+
+        let t3 = cos_squared * a_squared_plus_b_squared + sin_squared * sin_squared;
+        let t4 = t2 * sin_squared;
+        let parallel = perpendicular * (t3 - t4) / (t3 + t4);
+
+        (parallel + perpendicular) / 2.0
+    };
+
+    let (eta_r, eta_g, eta_b) = eta.get_point();
+    let (k_r, k_g, k_b) = k.get_point();
+
+    Color::new(
+        reflectance(eta_r, k_r),
+        reflectance(eta_g, k_g),
+        reflectance(eta_b, k_b),
+    )
+}
+
+// NOTE: Mirrors wo about the microfacet normal h, both pointing away from the surface
+pub fn reflect_about(wo: Vector, h: Vector) -> Vector {
+    h.scale(2.0 * dot_product(wo, h)).subv(wo)
+}
+
+// NOTE: Bends wo through the microfacet normal h into the far side, None on total
+// internal reflection
+pub fn refract_about(wo: Vector, h: Vector, eta: f64) -> Option<Vector> {
+    let cos_i = dot_product(wo, h);
+    let sin_t_squared = f64::max(1.0 - cos_i * cos_i, 0.0) / (eta * eta);
+
+    if sin_t_squared >= 1.0 {
+        return None;
+    }
+
+    let cos_t = (1.0 - sin_t_squared).sqrt();
+
+    Some(
+        wo.negate()
+            .scale(1.0 / eta)
+            .addv(h.scale(cos_i / eta - cos_t)),
+    )
+}
+
+// NOTE: Walter et al.'s rough dielectric, BSDF (not yet times the cosine) for light
+// from wi leaving towards wo. wi below the surface is light coming through it.
+pub fn rough_dielectric_f(wo: Vector, wi: Vector, alpha: f64, eta: f64) -> f64 {
+    let Some((h, reflected)) = dielectric_half_vector(wo, wi, eta) else {
+        return 0.0;
+    };
+    let (_, _, cos_o) = wo.get_point();
+    let (_, _, cos_i) = wi.get_point();
+    let (wo_h, wi_h) = (dot_product(wo, h), dot_product(wi, h));
+
+    let fresnel = fresnel_dielectric(wo_h, eta);
+    let d_g = ggx_d(h, alpha) * ggx_g(wo, wi, alpha);
+
+    if reflected {
+        fresnel * d_g / (4.0 * cos_o * cos_i).abs()
+    } else {
+        // NOTE: Radiance gets squeezed into a smaller cone going into the denser side,
+        // hence the 1 / eta^2
+        let denominator = (wi_h + wo_h / eta).powi(2) * cos_o * cos_i;
+
+        (1.0 - fresnel) * d_g * (wi_h * wo_h / denominator).abs() / (eta * eta)
+    }
+}
+
+// NOTE: Density of sample_rough_dielectric returning wi
+pub fn rough_dielectric_pdf(wo: Vector, wi: Vector, alpha: f64, eta: f64) -> f64 {
+    let Some((h, reflected)) = dielectric_half_vector(wo, wi, eta) else {
+        return 0.0;
+    };
+    let (wo_h, wi_h) = (dot_product(wo, h), dot_product(wi, h));
+
+    let fresnel = fresnel_dielectric(wo_h, eta);
+    let h_pdf = ggx_vndf_pdf(wo, h, alpha);
+
+    if reflected {
+        fresnel * h_pdf / (4.0 * wo_h.abs())
+    } else {
+        let jacobian = wi_h.abs() / (wi_h + wo_h / eta).powi(2);
+
+        (1.0 - fresnel) * h_pdf * jacobian
+    }
+}
+
+// NOTE: Picks a visible microfacet, then reflects or refracts through it in proportion
+// to its Fresnel reflectance. None when the reflection ends up below the surface.
+pub fn sample_rough_dielectric(
+    wo: Vector,
+    alpha: f64,
+    eta: f64,
+    u: (f64, f64),
+    choice: f64,
+) -> Option<Vector> {
+    let h = sample_ggx_vndf(wo, alpha, u);
+    let fresnel = fresnel_dielectric(dot_product(wo, h), eta);
+
+    let wi = if choice < fresnel {
+        reflect_about(wo, h)
+    } else {
+        refract_about(wo, h, eta)?
+    };
+
+    let (_, _, cos_i) = wi.get_point();
+    let reflected = choice < fresnel;
+
+    if (reflected && cos_i <= 0.0) || (!reflected && cos_i >= 0.0) {
+        None
+    } else {
+        Some(wi)
+    }
+}
+
+// NOTE: The microfacet normal that takes wo to wi, facing up, and whether that's a
+// reflection. None for pairs no microfacet can connect.
+fn dielectric_half_vector(wo: Vector, wi: Vector, eta: f64) -> Option<(Vector, bool)> {
+    let (_, _, cos_o) = wo.get_point();
+    let (_, _, cos_i) = wi.get_point();
+
+    if cos_o == 0.0 || cos_i == 0.0 {
+        return None;
+    }
+
+    let reflected = cos_i > 0.0;
+    let h = if reflected {
+        wo.addv(wi)
+    } else {
+        wi.scale(eta).addv(wo)
+    };
+
+    if h.get_length_squared() == 0.0 {
+        return None;
+    }
+
+    let h = h.unit();
+    let h = if h.get_point().2 < 0.0 { h.negate() } else { h };
+
+    // NOTE: Microfacets facing away from either direction can't be the one used
+    if dot_product(h, wi) * cos_i < 0.0 || dot_product(h, wo) * cos_o < 0.0 {
+        return None;
+    }
+
+    Some((h, reflected))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::functions::{random_double, seed_rng};
+
+    #[test]
+    fn fresnel_dielectric_matches_known_values() {
+        // NOTE: ((1 - 1.5) / (1 + 1.5))^2 head on, from either side
+        assert!((fresnel_dielectric(1.0, 1.5) - 0.04).abs() < 1e-12);
+        assert!((fresnel_dielectric(1.0, 1.0 / 1.5) - 0.04).abs() < 1e-12);
+        assert!((fresnel_dielectric(-1.0, 1.5) - 0.04).abs() < 1e-12);
+
+        assert!((fresnel_dielectric(0.0, 1.5) - 1.0).abs() < 1e-12);
+        // NOTE: Past the critical angle of about 41.8 degrees inside glass
+        assert_eq!(fresnel_dielectric(0.5, 1.0 / 1.5), 1.0);
+        assert!(fresnel_dielectric(0.3, 1.0) < 1e-12);
+    }
+
+    #[test]
+    fn fresnel_conductor_matches_known_values() {
+        let (eta, k) = (Color::new(1.5, 0.2, 1.0), Color::new(0.0, 3.0, 1.0));
+
+        // NOTE: ((n - 1)^2 + k^2) / ((n + 1)^2 + k^2) head on
+        let (r, g, b) = fresnel_conductor(1.0, eta, k).get_point();
+        assert!((r - 0.04).abs() < 1e-12);
+        assert!((g - 9.64 / 10.44).abs() < 1e-12);
+        assert!((b - 0.2).abs() < 1e-12);
+
+        // NOTE: Without absorption it's just a dielectric
+        for cosine in [0.1, 0.5, 0.9] {
+            let (r, _, _) = fresnel_conductor(cosine, eta, k).get_point();
+            assert!((r - fresnel_dielectric(cosine, 1.5)).abs() < 1e-9);
+        }
+
+        let (r, g, b) = fresnel_conductor(0.0, eta, k).get_point();
+        assert!((r - 1.0).abs() < 1e-12 && (g - 1.0).abs() < 1e-12 && (b - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn visible_normals_face_the_viewer_with_matching_density() {
+        seed_rng(1);
+
+        let wo = Vector::new(0.6, -0.2, 0.5).unit();
+        let alpha = 0.3;
+        let runs = 100_000;
+
+        // NOTE: The density over hemisphere directions should integrate to one
+        let mut total = 0.0;
+        for _ in 0..runs {
+            let h = sample_ggx_vndf(wo, alpha, (random_double(), random_double()));
+            assert!(dot_product(wo, h) >= 0.0 && h.get_point().2 >= 0.0);
+
+            let (u1, u2) = (random_double(), random_double());
+            let z = u1;
+            let r = (1.0 - z * z).sqrt();
+            let uniform = Vector::new(r * (2.0 * PI * u2).cos(), r * (2.0 * PI * u2).sin(), z);
+            total += ggx_vndf_pdf(wo, uniform, alpha) * 2.0 * PI;
+        }
+
+        assert!((total / runs as f64 - 1.0).abs() < 0.03);
+    }
+
+    #[test]
+    fn refraction_follows_snells_law() {
+        let wo = Vector::new(0.5, 0.0, (1.0_f64 - 0.25).sqrt());
+        let h = Vector::new(0.0, 0.0, 1.0);
+        let wi = refract_about(wo, h, 1.5).unwrap();
+
+        let (x, _, z) = wi.get_point();
+        assert!(z < 0.0);
+        assert!((x.abs() * 1.5 - 0.5).abs() < 1e-12);
+        assert!((wi.get_length() - 1.0).abs() < 1e-12);
+
+        assert!(refract_about(Vector::new(0.9, 0.0, 0.19_f64.sqrt()), h, 1.0 / 1.5).is_none());
+    }
+}
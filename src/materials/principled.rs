@@ -0,0 +1,512 @@
+use std::sync::Arc;
+
+use crate::{
+    materials::{
+        microfacet::{
+            ggx_d, ggx_g, ggx_vndf_pdf, gtr1_d, reflect_about, rough_dielectric_f,
+            rough_dielectric_pdf, roughness_to_alpha, sample_ggx_vndf, sample_gtr1,
+            sample_rough_dielectric, schlick_fresnel, schlick_weight,
+        },
+        scatterable::{ScatterRecord, Scatterable},
+    },
+    objects::hittable::HitRecord,
+    pdf::{Pdfs, pdf::Pdf},
+    ray::Ray,
+    scene_file::writer::SceneWriter,
+    texture::{solid_color::SolidColorTexture, texture::Texture},
+    utils::{constants::PI, functions::random_double, onb::Onb},
+    vector::{Color, Point, Vector, dot_product, get_random_cosine_direction},
+};
+
+// NOTE: Burley's principled BSDF as used by Disney (and most DCC tools since), one
+// material that goes from rough plastic to polished metal to frosted glass with a
+// handful of 0 to 1 parameters. Every parameter can come from a texture, the scalar
+// ones read the texture's luminance.
+#[derive(Clone)]
+pub struct PrincipledMaterial {
+    base_color: Arc<dyn Texture>,
+    metallic: Arc<dyn Texture>,
+    roughness: Arc<dyn Texture>,
+    // NOTE: Strength of the reflection off the non-metal part, 0.5 is the usual 4%
+    specular: Arc<dyn Texture>,
+    // NOTE: Soft rim on cloth-like surfaces
+    sheen: Arc<dyn Texture>,
+    // NOTE: A second, colourless glossy layer on top like varnish or car paint
+    clearcoat: Arc<dyn Texture>,
+    clearcoat_gloss: Arc<dyn Texture>,
+    // NOTE: How much of the non-metal part lets light through like glass
+    transmission: Arc<dyn Texture>,
+    refraction_index: f64,
+}
+
+impl PrincipledMaterial {
+    // NOTE: Starts out as a dielectric of medium roughness, the same defaults Blender uses
+    pub fn new(base_color: Arc<dyn Texture>) -> PrincipledMaterial {
+        PrincipledMaterial {
+            base_color,
+            metallic: constant(0.0),
+            roughness: constant(0.5),
+            specular: constant(0.5),
+            sheen: constant(0.0),
+            clearcoat: constant(0.0),
+            clearcoat_gloss: constant(1.0),
+            transmission: constant(0.0),
+            refraction_index: 1.5,
+        }
+    }
+
+    pub fn metallic(mut self, metallic: Arc<dyn Texture>) -> PrincipledMaterial {
+        self.metallic = metallic;
+        self
+    }
+
+    pub fn roughness(mut self, roughness: Arc<dyn Texture>) -> PrincipledMaterial {
+        self.roughness = roughness;
+        self
+    }
+
+    pub fn specular(mut self, specular: Arc<dyn Texture>) -> PrincipledMaterial {
+        self.specular = specular;
+        self
+    }
+
+    pub fn sheen(mut self, sheen: Arc<dyn Texture>) -> PrincipledMaterial {
+        self.sheen = sheen;
+        self
+    }
+
+    pub fn clearcoat(
+        mut self,
+        clearcoat: Arc<dyn Texture>,
+        clearcoat_gloss: Arc<dyn Texture>,
+    ) -> PrincipledMaterial {
+        self.clearcoat = clearcoat;
+        self.clearcoat_gloss = clearcoat_gloss;
+        self
+    }
+
+    pub fn transmission(
+        mut self,
+        transmission: Arc<dyn Texture>,
+        refraction_index: f64,
+    ) -> PrincipledMaterial {
+        self.transmission = transmission;
+        self.refraction_index = refraction_index;
+        self
+    }
+
+    pub fn to_scene_definition(&self, writer: &mut SceneWriter) -> String {
+        format!(
+            "principled {} metallic={} roughness={} specular={} sheen={} \
+             clearcoat={} clearcoat_gloss={} transmission={} refraction_index={}",
+            writer.add_color_or_texture(&self.base_color),
+            writer.add_number_or_texture(&self.metallic),
+            writer.add_number_or_texture(&self.roughness),
+            writer.add_number_or_texture(&self.specular),
+            writer.add_number_or_texture(&self.sheen),
+            writer.add_number_or_texture(&self.clearcoat),
+            writer.add_number_or_texture(&self.clearcoat_gloss),
+            writer.add_number_or_texture(&self.transmission),
+            self.refraction_index
+        )
+    }
+
+    // NOTE: Every parameter looked up at the hit
+    fn get_bsdf(&self, ray: &Ray, hit_record: &HitRecord) -> PrincipledBsdf {
+        let (u, v) = hit_record.get_texture_coordinates();
+        let point = hit_record.get_point();
+        let scalar = |texture: &Arc<dyn Texture>| get_scalar(texture, u, v, point);
+
+        let basis = Onb::new(hit_record.get_normal());
+        let roughness = scalar(&self.roughness);
+
+        // NOTE: The normal always faces the incoming ray, so going in or coming out
+        // only changes which way the refraction index is taken
+        let eta = if hit_record.get_front() {
+            self.refraction_index
+        } else {
+            1.0 / self.refraction_index
+        };
+
+        PrincipledBsdf {
+            basis,
+            wo: basis.inverse_transform(ray.get_direction().unit().negate()),
+            base_color: self.base_color.get_value(u, v, point),
+            metallic: scalar(&self.metallic),
+            roughness,
+            alpha: roughness_to_alpha(roughness),
+            specular: scalar(&self.specular),
+            sheen: scalar(&self.sheen),
+            clearcoat: scalar(&self.clearcoat),
+            clearcoat_alpha: 0.1 - 0.099 * scalar(&self.clearcoat_gloss),
+            transmission: scalar(&self.transmission),
+            eta,
+        }
+    }
+}
+
+impl Scatterable for PrincipledMaterial {
+    fn scatter(&self, ray: Ray, hit_record: HitRecord) -> Option<ScatterRecord> {
+        Some(ScatterRecord::Sampled(Pdfs::Principled(
+            self.get_bsdf(&ray, &hit_record),
+        )))
+    }
+
+    fn evaluate(&self, ray: &Ray, hit_record: &HitRecord, direction: Vector) -> Color {
+        self.get_bsdf(ray, hit_record).evaluate(direction)
+    }
+}
+
+// NOTE: The material's parameters at one hit, both evaluates the BSDF and samples it
+#[derive(Clone, Copy)]
+pub struct PrincipledBsdf {
+    basis: Onb,
+    // NOTE: Towards the viewer, in the local frame of basis
+    wo: Vector,
+    base_color: Color,
+    metallic: f64,
+    roughness: f64,
+    alpha: f64,
+    specular: f64,
+    sheen: f64,
+    clearcoat: f64,
+    clearcoat_alpha: f64,
+    transmission: f64,
+    eta: f64,
+}
+
+impl PrincipledBsdf {
+    pub fn evaluate(&self, direction: Vector) -> Color {
+        let black = Color::new(0.0, 0.0, 0.0);
+        let white = Color::new(1.0, 1.0, 1.0);
+
+        let (wo, wi) = (self.wo, self.basis.inverse_transform(direction.unit()));
+        let (cos_o, cos_i) = (wo.get_point().2, wi.get_point().2);
+
+        if cos_o <= 0.0 || cos_i == 0.0 {
+            return black;
+        }
+
+        let (diffuse_weight, specular_weight, transmission_weight) = self.get_layer_weights();
+        let mut f = black;
+
+        if cos_i > 0.0 {
+            let h = wo.addv(wi).unit();
+            let cos_d = dot_product(wi, h);
+
+            if diffuse_weight > 0.0 {
+                // NOTE: Burley's diffuse, rough surfaces get brighter towards grazing
+                // angles and smooth ones darker
+                let fd90 = 0.5 + 2.0 * self.roughness * cos_d * cos_d;
+                let retro_reflection = (1.0 + (fd90 - 1.0) * schlick_weight(cos_i))
+                    * (1.0 + (fd90 - 1.0) * schlick_weight(cos_o));
+                let diffuse = self.base_color.scale(retro_reflection / PI);
+
+                let sheen_color = white.addv(self.get_tint()).scale(0.5);
+                let sheen = sheen_color.scale(self.sheen * schlick_weight(cos_d));
+
+                f = f.addv(diffuse.addv(sheen).scale(diffuse_weight));
+            }
+
+            if specular_weight > 0.0 {
+                let fresnel = schlick_fresnel(self.get_specular_color(), cos_d);
+                let d_g = ggx_d(h, self.alpha) * ggx_g(wo, wi, self.alpha);
+
+                f = f.addv(fresnel.scale(specular_weight * d_g / (4.0 * cos_o * cos_i)));
+            }
+
+            if self.clearcoat > 0.0 {
+                let fresnel = 0.04 + 0.96 * schlick_weight(cos_d);
+                let d_g = gtr1_d(h, self.clearcoat_alpha) * ggx_g(wo, wi, 0.25);
+
+                f = f.addv(
+                    white.scale(0.25 * self.clearcoat * fresnel * d_g / (4.0 * cos_o * cos_i)),
+                );
+            }
+        }
+
+        if transmission_weight > 0.0 {
+            // NOTE: Light going through picks up the base colour, reflections don't.
+            // Half of it (the square root) at each surface, so coming in and going
+            // back out of a closed object tints it by the base colour once.
+            let tint = if cos_i > 0.0 {
+                white
+            } else {
+                let (r, g, b) = self.base_color.get_point();
+                Color::new(r.max(0.0).sqrt(), g.max(0.0).sqrt(), b.max(0.0).sqrt())
+            };
+            let bsdf = rough_dielectric_f(wo, wi, self.alpha, self.eta);
+
+            f = f.addv(tint.scale(transmission_weight * bsdf));
+        }
+
+        f.scale(cos_i.abs())
+    }
+
+    // NOTE: Returns (diffuse, specular, transmission), the metal part is all specular
+    // and the rest is split between diffuse and glass
+    fn get_layer_weights(&self) -> (f64, f64, f64) {
+        let dielectric = 1.0 - self.metallic;
+        let transmission = dielectric * self.transmission;
+
+        (
+            dielectric * (1.0 - self.transmission),
+            1.0 - transmission,
+            transmission,
+        )
+    }
+
+    // NOTE: Reflectance head on, from about 4% for plastics up to the base colour for
+    // metals
+    fn get_specular_color(&self) -> Color {
+        let dielectric = Color::new(1.0, 1.0, 1.0).scale(0.08 * self.specular);
+
+        dielectric
+            .scale(1.0 - self.metallic)
+            .addv(self.base_color.scale(self.metallic))
+    }
+
+    // NOTE: The base colour's hue without its brightness
+    fn get_tint(&self) -> Color {
+        let luminance = self.base_color.luminance();
+
+        if luminance > 0.0 {
+            self.base_color.scale(1.0 / luminance)
+        } else {
+            Color::new(1.0, 1.0, 1.0)
+        }
+    }
+
+    // NOTE: How likely each lobe is to be sampled, as (diffuse, specular, clearcoat,
+    // transmission) adding up to one. The specular lobe gets more of them the more the
+    // surface reflects, but never so few that highlights turn to fireflies.
+    fn get_lobe_probabilities(&self) -> (f64, f64, f64, f64) {
+        let (diffuse_weight, specular_weight, transmission_weight) = self.get_layer_weights();
+        let reflectance = schlick_fresnel(self.get_specular_color(), self.wo.get_point().2);
+
+        let diffuse = diffuse_weight;
+        let specular = specular_weight * reflectance.luminance().clamp(0.25, 1.0);
+        let clearcoat = 0.25 * self.clearcoat;
+        let transmission = transmission_weight;
+
+        let total = diffuse + specular + clearcoat + transmission;
+        if total <= 0.0 {
+            return (0.0, 0.0, 0.0, 0.0);
+        }
+
+        (
+            diffuse / total,
+            specular / total,
+            clearcoat / total,
+            transmission / total,
+        )
+    }
+}
+
+impl Pdf for PrincipledBsdf {
+    fn get_value(&self, direction: Vector) -> f64 {
+        let (wo, wi) = (self.wo, self.basis.inverse_transform(direction.unit()));
+        let (cos_o, cos_i) = (wo.get_point().2, wi.get_point().2);
+
+        if cos_o <= 0.0 {
+            return 0.0;
+        }
+
+        let (diffuse, specular, clearcoat, transmission) = self.get_lobe_probabilities();
+        let mut pdf = 0.0;
+
+        if cos_i > 0.0 {
+            let h = wo.addv(wi).unit();
+            let wo_h = dot_product(wo, h);
+
+            pdf += diffuse * cos_i / PI;
+
+            if wo_h > 0.0 {
+                pdf += specular * ggx_vndf_pdf(wo, h, self.alpha) / (4.0 * wo_h);
+                pdf += clearcoat * gtr1_d(h, self.clearcoat_alpha) * h.get_point().2 / (4.0 * wo_h);
+            }
+        }
+
+        if transmission > 0.0 {
+            pdf += transmission * rough_dielectric_pdf(wo, wi, self.alpha, self.eta);
+        }
+
+        pdf
+    }
+
+    fn generate(&self) -> Option<Vector> {
+        let wo = self.wo;
+
+        if wo.get_point().2 <= 0.0 {
+            return None;
+        }
+
+        let (diffuse, specular, clearcoat, _) = self.get_lobe_probabilities();
+        let choice = random_double();
+        let u = (random_double(), random_double());
+
+        let wi = if choice < diffuse {
+            get_random_cosine_direction()
+        } else if choice < diffuse + specular {
+            reflect_about(wo, sample_ggx_vndf(wo, self.alpha, u))
+        } else if choice < diffuse + specular + clearcoat {
+            // NOTE: Unlike the visible normals, these can face away from wo
+            let h = sample_gtr1(self.clearcoat_alpha, u);
+            if dot_product(wo, h) <= 0.0 {
+                return None;
+            }
+
+            reflect_about(wo, h)
+        } else {
+            sample_rough_dielectric(wo, self.alpha, self.eta, u, random_double())?
+        };
+
+        // NOTE: Reflections off a microfacet can still end up below the surface
+        let is_transmission = choice >= diffuse + specular + clearcoat;
+        if !is_transmission && wi.get_point().2 <= 0.0 {
+            return None;
+        }
+
+        Some(self.basis.transform(wi))
+    }
+}
+
+fn constant(value: f64) -> Arc<dyn Texture> {
+    Arc::new(SolidColorTexture::new_from_rgb(value, value, value))
+}
+
+fn get_scalar(texture: &Arc<dyn Texture>, u: f64, v: f64, point: Point) -> f64 {
+    texture.get_value(u, v, point).luminance().clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils::functions::seed_rng, vector::get_random_unit_vector};
+
+    // NOTE: Seen from wo in the frame of the z-axis, so local and world coordinates match
+    fn bsdf(
+        wo: Vector,
+        base_color: Color,
+        (metallic, roughness, transmission): (f64, f64, f64),
+        (sheen, clearcoat): (f64, f64),
+        eta: f64,
+    ) -> PrincipledBsdf {
+        PrincipledBsdf {
+            basis: Onb::new(Vector::new(0.0, 0.0, 1.0)),
+            wo: wo.unit(),
+            base_color,
+            metallic,
+            roughness,
+            alpha: roughness_to_alpha(roughness),
+            specular: 0.5,
+            sheen,
+            clearcoat,
+            clearcoat_alpha: 0.05,
+            transmission,
+            eta,
+        }
+    }
+
+    // NOTE: Returns (estimate from the BSDF's own samples, estimate from uniform
+    // directions, integral of the pdf), all of the luminance of f * cos. The first
+    // two only agree if generate really draws directions with density get_value.
+    fn estimates(bsdf: &PrincipledBsdf, runs: usize) -> (f64, f64, f64) {
+        let mut sampled = 0.0;
+        for _ in 0..runs {
+            if let Some(direction) = bsdf.generate() {
+                let pdf = bsdf.get_value(direction);
+                if pdf > 0.0 {
+                    sampled += bsdf.evaluate(direction).luminance() / pdf;
+                }
+            }
+        }
+
+        let (mut uniform, mut pdf_total) = (0.0, 0.0);
+        for _ in 0..runs {
+            let direction = get_random_unit_vector();
+            uniform += bsdf.evaluate(direction).luminance() * 4.0 * PI;
+            pdf_total += bsdf.get_value(direction) * 4.0 * PI;
+        }
+
+        let n = runs as f64;
+        (sampled / n, uniform / n, pdf_total / n)
+    }
+
+    #[test]
+    fn samples_agree_with_the_evaluated_bsdf() {
+        seed_rng(1);
+
+        let wo = Vector::new(0.3, 0.2, 0.9);
+        let color = Color::new(0.8, 0.6, 0.4);
+
+        for (name, bsdf) in [
+            ("diffuse", bsdf(wo, color, (0.0, 0.8, 0.0), (0.0, 0.0), 1.5)),
+            ("plastic", bsdf(wo, color, (0.0, 0.4, 0.0), (0.5, 0.8), 1.5)),
+            ("metal", bsdf(wo, color, (1.0, 0.4, 0.0), (0.0, 0.0), 1.5)),
+            ("glass", bsdf(wo, color, (0.0, 0.9, 1.0), (0.0, 0.0), 1.5)),
+            (
+                "glass from inside",
+                bsdf(wo, color, (0.0, 0.9, 1.0), (0.0, 0.0), 1.0 / 1.5),
+            ),
+        ] {
+            let (sampled, uniform, pdf_total) = estimates(&bsdf, 300_000);
+
+            assert!(
+                (sampled - uniform).abs() < 0.03 * uniform,
+                "{}: sampled {} against uniform {}",
+                name,
+                sampled,
+                uniform
+            );
+            assert!(
+                pdf_total < 1.01,
+                "{}: pdf integrates to {}",
+                name,
+                pdf_total
+            );
+        }
+    }
+
+    #[test]
+    fn passing_through_both_sides_tints_by_the_base_color_once() {
+        seed_rng(1);
+
+        let color = Color::new(0.81, 0.25, 0.04);
+        let white = Color::new(1.0, 1.0, 1.0);
+        let wo = Vector::new(0.3, 0.2, 0.9);
+
+        for eta in [1.5, 1.0 / 1.5] {
+            let tinted = bsdf(wo, color, (0.0, 0.5, 1.0), (0.0, 0.0), eta);
+            let clear = bsdf(wo, white, (0.0, 0.5, 1.0), (0.0, 0.0), eta);
+
+            let direction = std::iter::repeat_with(|| tinted.generate())
+                .flatten()
+                .find(|direction| direction.get_point().2 < 0.0)
+                .unwrap();
+            let (r, g, b) = tinted.evaluate(direction).get_point();
+            let (r_clear, g_clear, b_clear) = clear.evaluate(direction).get_point();
+
+            // NOTE: Going in and coming back out multiplies the two
+            let (r, g, b) = (r / r_clear, g / g_clear, b / b_clear);
+            assert!((r * r - 0.81).abs() < 1e-9);
+            assert!((g * g - 0.25).abs() < 1e-9);
+            assert!((b * b - 0.04).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn nothing_scatters_from_behind_the_surface() {
+        let bsdf = bsdf(
+            Vector::new(0.0, 0.3, -0.9),
+            Color::new(0.8, 0.8, 0.8),
+            (0.0, 0.5, 0.0),
+            (0.0, 0.0),
+            1.5,
+        );
+
+        assert!(bsdf.generate().is_none());
+        assert!(bsdf.evaluate(Vector::new(0.0, 0.0, 1.0)).is_zero());
+        assert_eq!(bsdf.get_value(Vector::new(0.0, 0.0, 1.0)), 0.0);
+    }
+}
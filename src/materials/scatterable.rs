@@ -1,24 +1,26 @@
-use crate::{objects::hittable::HitRecord, ray::Ray, vector::Color};
+use crate::{
+    objects::hittable::HitRecord,
+    pdf::Pdfs,
+    ray::Ray,
+    vector::{Color, Vector},
+};
 
-pub struct ScatterRecord {
-    ray: Ray,
-    attenuation: Color,
-}
-
-impl ScatterRecord {
-    pub fn new(ray: Ray, attenuation: Color) -> ScatterRecord {
-        ScatterRecord { ray, attenuation }
-    }
-
-    pub fn get_ray(&self) -> Ray {
-        self.ray
-    }
-
-    pub fn get_attenuation(&self) -> Color {
-        self.attenuation
-    }
+pub enum ScatterRecord {
+    // NOTE: Mirror-like materials only ever scatter one way, so there's just the ray
+    // and how much of the light along it comes back
+    Specular { ray: Ray, attenuation: Color },
+    // NOTE: Everything else hands back the distribution to draw a direction from, how
+    // much light comes back from it is up to Scatterable::evaluate
+    Sampled(Pdfs),
 }
 
 pub trait Scatterable {
     fn scatter(&self, ray: Ray, hit_record: HitRecord) -> Option<ScatterRecord>;
+
+    // NOTE: The BSDF times the cosine with the normal, how much of the light arriving
+    // from direction (per solid angle) is scattered back along the ray. Specular
+    // materials have no density to speak of and leave it black.
+    fn evaluate(&self, _ray: &Ray, _hit_record: &HitRecord, _direction: Vector) -> Color {
+        Color::new(0.0, 0.0, 0.0)
+    }
 }
@@ -5,6 +5,7 @@ use crate::{
     materials::Materials,
     objects::hittable::{HitRecord, Hittable},
     ray::Ray,
+    scene_file::writer::{Placement, SceneWriter},
     utils::{
         constants::F_INF,
         functions::random_double,
@@ -74,7 +75,6 @@ impl Hittable for ConstantMedium {
 
         // NOTE: Arbitrary Values (Normal, front face and texture coordinates don't matter)
         let normal = Vector::new(1.0, 0.0, 0.0);
-        let front = true;
         let u = 0.0;
         let v = 0.0;
 
@@ -96,4 +96,17 @@ impl Hittable for ConstantMedium {
     fn clone_box(&self) -> Arc<dyn Hittable> {
         Arc::new(self.clone())
     }
+
+    fn write_scene(&self, writer: &mut SceneWriter, placement: &Placement) {
+        let boundary = writer.add_named_object(self.boundary.as_ref());
+        let material = writer.add_material(&self.material);
+
+        writer.push_object(
+            format!(
+                "medium boundary={} density={} material={}",
+                boundary, self.optical_density, material
+            ),
+            placement,
+        );
+    }
 }
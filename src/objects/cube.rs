@@ -2,10 +2,7 @@ use std::sync::Arc;
 
 use crate::{
     materials::Materials,
-    objects::{
-        hittable::{Hittable, HittableList},
-        quad::Quad,
-    },
+    objects::{hittable::HittableList, quad::Quad},
     transformation::{rotation::Rotation, translation::Translation},
     vector::{Point, Vector},
 };
@@ -102,7 +99,7 @@ impl Cube {
         self.sides = hittable_list;
     }
 
-    pub fn to_hittable_list(self) -> HittableList {
+    pub fn into_hittable_list(self) -> HittableList {
         self.sides
     }
 }
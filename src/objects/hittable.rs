@@ -4,7 +4,8 @@ use crate::{
     bvh::aabb::{Aabb, merge_aabb},
     materials::Materials,
     ray::Ray,
-    utils::interval::{EMPTY_INTERVAL, Interval},
+    scene_file::writer::{Placement, SceneWriter},
+    utils::{functions::random_int_in_range, interval::Interval},
     vector::{Point, Vector, dot_product},
 };
 
@@ -12,6 +13,22 @@ pub trait Hittable: Send + Sync {
     fn hit(&self, ray: &Ray, interval: &Interval) -> Option<HitRecord>;
     fn get_aabb(&self) -> Aabb;
     fn clone_box(&self) -> Arc<dyn Hittable>;
+    fn write_scene(&self, writer: &mut SceneWriter, placement: &Placement);
+
+    // NOTE: Adds every light (object with an emissive material) in here to lights, so
+    // they can be sampled directly instead of waiting for a ray to stumble onto them
+    fn collect_lights(&self, _lights: &mut HittableList) {}
+
+    // NOTE: Density (per solid angle) of random_direction picking ray's direction from
+    // ray's origin, 0 when the ray misses. Only lights need these two.
+    fn pdf_value(&self, _ray: &Ray) -> f64 {
+        0.0
+    }
+
+    // NOTE: A direction from origin towards a random point on this object
+    fn random_direction(&self, _origin: Point, _time: f64) -> Vector {
+        Vector::new(1.0, 0.0, 0.0)
+    }
 }
 
 #[derive(Clone)]
@@ -161,6 +178,10 @@ impl HittableList {
     pub fn get_hittables(self) -> Vec<Arc<dyn Hittable>> {
         self.hittable_list
     }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<dyn Hittable>> {
+        self.hittable_list.iter()
+    }
 }
 
 impl Hittable for HittableList {
@@ -194,6 +215,41 @@ impl Hittable for HittableList {
     fn clone_box(&self) -> Arc<dyn Hittable> {
         Arc::new(self.clone())
     }
+
+    fn write_scene(&self, writer: &mut SceneWriter, placement: &Placement) {
+        let child_placement = placement.for_children();
+
+        for hittable in &self.hittable_list {
+            hittable.write_scene(writer, &child_placement);
+        }
+    }
+
+    fn collect_lights(&self, lights: &mut HittableList) {
+        for hittable in &self.hittable_list {
+            hittable.collect_lights(lights);
+        }
+    }
+
+    // NOTE: Each object is picked with the same chance, so the density is the average
+    fn pdf_value(&self, ray: &Ray) -> f64 {
+        if self.hittable_list.is_empty() {
+            return 0.0;
+        }
+
+        let total: f64 = self
+            .hittable_list
+            .iter()
+            .map(|hittable| hittable.pdf_value(ray))
+            .sum();
+
+        total / self.hittable_list.len() as f64
+    }
+
+    fn random_direction(&self, origin: Point, time: f64) -> Vector {
+        let index = random_int_in_range(0, self.hittable_list.len() as i64) as usize;
+
+        self.hittable_list[index].random_direction(origin, time)
+    }
 }
 
 pub fn hit_front(ray: &Ray, out_normal: Vector) -> bool {
@@ -2,10 +2,11 @@ use std::sync::Arc;
 
 use crate::{
     bvh::aabb::{Aabb, merge_aabb},
-    materials::{Materials, lambertian::LambertianMaterial},
-    objects::hittable::{HitRecord, Hittable},
+    materials::Materials,
+    objects::hittable::{HitRecord, Hittable, HittableList},
     ray::Ray,
-    utils::interval::Interval,
+    scene_file::writer::{Placement, SceneWriter, format_vector},
+    utils::{constants::F_INF, functions::random_double, interval::Interval},
     vector::{Point, Vector, cross_product, dot_product},
 };
 
@@ -18,6 +19,7 @@ pub struct Quad {
     w: Vector,
     normal: Vector,
     d: f64,
+    area: f64,
     material: Materials,
     bounding_box: Aabb,
 }
@@ -38,6 +40,7 @@ impl Quad {
         let d: f64 = dot_product(normal, q);
 
         let w = n.scale(1.0 / dot_product(n, n));
+        let area = n.get_length();
 
         Quad {
             q,
@@ -46,6 +49,7 @@ impl Quad {
             w,
             normal,
             d,
+            area,
             material,
             bounding_box,
         }
@@ -71,7 +75,7 @@ impl Hittable for Quad {
         let alpha: f64 = dot_product(self.w, cross_product(hit_vector, self.v));
         let beta: f64 = dot_product(self.w, cross_product(self.u, hit_vector));
 
-        let unit_interval = Interval::new(-1.0, 1.0);
+        let unit_interval = Interval::new(0.0, 1.0);
         if unit_interval.contains(alpha) && unit_interval.contains(beta) {
             Some(HitRecord::new(
                 intersection,
@@ -94,4 +98,256 @@ impl Hittable for Quad {
     fn clone_box(&self) -> Arc<dyn Hittable> {
         Arc::new(self.clone())
     }
+
+    fn write_scene(&self, writer: &mut SceneWriter, placement: &Placement) {
+        let material = writer.add_material(&self.material);
+
+        writer.push_object(
+            format!(
+                "quad q={} u={} v={} material={}",
+                format_vector(self.q),
+                format_vector(self.u),
+                format_vector(self.v),
+                material
+            ),
+            placement,
+        );
+    }
+
+    fn collect_lights(&self, lights: &mut HittableList) {
+        if self.material.is_emissive() {
+            lights.add_hittable(self.clone_box());
+        }
+    }
+
+    // NOTE: Points are picked evenly over the area, which turns into a density per solid
+    // angle of distance^2 / (cos * area) as seen from the ray's origin
+    fn pdf_value(&self, ray: &Ray) -> f64 {
+        let Some(hit) = self.hit(ray, &Interval::new(0.001, F_INF)) else {
+            return 0.0;
+        };
+
+        let direction = ray.get_direction();
+        let distance_squared = hit.get_t() * hit.get_t() * direction.get_length_squared();
+        let cosine = (dot_product(direction, self.normal) / direction.get_length()).abs();
+
+        if cosine < 1e-8 {
+            return 0.0;
+        }
+
+        distance_squared / (cosine * self.area)
+    }
+
+    fn random_direction(&self, origin: Point, _time: f64) -> Vector {
+        let point = self
+            .q
+            .addv(self.u.scale(random_double()))
+            .addv(self.v.scale(random_double()));
+
+        point.subv(origin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        materials::lambertian::LambertianMaterial, texture::solid_color::SolidColorTexture,
+    };
+
+    const EPSILON: f64 = 1e-9;
+
+    fn material() -> Materials {
+        Materials::Lambertian(LambertianMaterial::new(Arc::new(
+            SolidColorTexture::new_from_rgb(0.5, 0.5, 0.5),
+        )))
+    }
+
+    // NOTE: The unit square in the z = 0 plane, facing +z
+    fn unit_quad() -> Quad {
+        Quad::new(
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(1.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+            material(),
+        )
+    }
+
+    fn ray_at(x: f64, y: f64, direction_z: f64) -> Ray {
+        Ray::new(
+            Point::new(x, y, -direction_z.signum() * 5.0),
+            Vector::new(0.0, 0.0, direction_z),
+            None,
+        )
+    }
+
+    fn forward() -> Interval {
+        Interval::new(0.001, F_INF)
+    }
+
+    #[test]
+    fn hit_gives_the_plane_coordinates() {
+        let hit = unit_quad()
+            .hit(&ray_at(0.25, 0.75, -1.0), &forward())
+            .expect("expected a hit");
+
+        let (u, v) = hit.get_texture_coordinates();
+        assert!((u - 0.25).abs() < EPSILON && (v - 0.75).abs() < EPSILON);
+        assert!((hit.get_t() - 5.0).abs() < EPSILON);
+        assert!(hit.get_front());
+
+        let (_, _, z) = hit.get_normal().get_point();
+        assert!((z - 1.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn hit_from_behind_faces_back() {
+        let hit = unit_quad()
+            .hit(&ray_at(0.5, 0.5, 1.0), &forward())
+            .expect("expected a hit");
+
+        assert!(!hit.get_front());
+        let (_, _, z) = hit.get_normal().get_point();
+        assert!((z + 1.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn edges_and_corners_count_as_hits() {
+        for (x, y) in [(0.0, 0.0), (1.0, 1.0), (0.0, 0.5), (0.5, 1.0)] {
+            assert!(unit_quad().hit(&ray_at(x, y, -1.0), &forward()).is_some());
+        }
+    }
+
+    #[test]
+    fn only_the_quad_itself_is_hit_not_the_whole_plane() {
+        let quad = unit_quad();
+
+        for (x, y) in [
+            (-0.5, 0.5),
+            (0.5, -0.5),
+            (-0.5, -0.5),
+            (1.5, 0.5),
+            (0.5, 1.01),
+        ] {
+            assert!(
+                quad.hit(&ray_at(x, y, -1.0), &forward()).is_none(),
+                "({}, {}) is outside the quad",
+                x,
+                y
+            );
+        }
+    }
+
+    #[test]
+    fn slanted_quad_uses_its_own_basis() {
+        let quad = Quad::new(
+            Point::new(1.0, 1.0, 0.0),
+            Vector::new(2.0, 0.0, 0.0),
+            Vector::new(1.0, 1.0, 0.0),
+            material(),
+        );
+
+        let hit = quad
+            .hit(&ray_at(2.5, 1.5, -1.0), &forward())
+            .expect("expected a hit");
+        let (u, v) = hit.get_texture_coordinates();
+        assert!((u - 0.5).abs() < EPSILON && (v - 0.5).abs() < EPSILON);
+
+        // NOTE: Inside the bounding box but outside the parallelogram
+        assert!(quad.hit(&ray_at(1.2, 1.8, -1.0), &forward()).is_none());
+    }
+
+    #[test]
+    fn parallel_rays_miss() {
+        let along = Ray::new(Point::new(-1.0, 0.5, 0.0), Vector::new(1.0, 0.0, 0.0), None);
+        let above = Ray::new(Point::new(-1.0, 0.5, 1.0), Vector::new(1.0, 0.0, 0.0), None);
+
+        assert!(unit_quad().hit(&along, &forward()).is_none());
+        assert!(unit_quad().hit(&above, &forward()).is_none());
+    }
+
+    #[test]
+    fn hits_behind_or_past_the_interval_are_ignored() {
+        assert!(
+            unit_quad()
+                .hit(&ray_at(0.5, 0.5, -1.0), &Interval::new(0.001, 4.0))
+                .is_none()
+        );
+        assert!(
+            unit_quad()
+                .hit(
+                    &Ray::new(Point::new(0.5, 0.5, 5.0), Vector::new(0.0, 0.0, 1.0), None),
+                    &forward()
+                )
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn degenerate_quads_are_never_hit() {
+        let parallel_sides = Quad::new(
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(1.0, 0.0, 0.0),
+            Vector::new(2.0, 0.0, 0.0),
+            material(),
+        );
+        let no_width = Quad::new(
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+            material(),
+        );
+
+        for quad in [parallel_sides, no_width] {
+            assert!(quad.hit(&ray_at(0.5, 0.0, -1.0), &forward()).is_none());
+            assert!(quad.hit(&ray_at(0.0, 0.5, -1.0), &forward()).is_none());
+        }
+    }
+
+    #[test]
+    fn nan_rays_never_hit() {
+        let nan_direction = Ray::new(
+            Point::new(0.5, 0.5, 5.0),
+            Vector::new(f64::NAN, 0.0, -1.0),
+            None,
+        );
+        let nan_origin = Ray::new(
+            Point::new(f64::NAN, 0.5, 5.0),
+            Vector::new(0.0, 0.0, -1.0),
+            None,
+        );
+
+        assert!(unit_quad().hit(&nan_direction, &forward()).is_none());
+        assert!(unit_quad().hit(&nan_origin, &forward()).is_none());
+    }
+
+    #[test]
+    fn light_samples_land_on_the_quad_with_matching_density() {
+        crate::utils::functions::seed_rng(1);
+
+        let quad = Quad::new(
+            Point::new(-1.0, 2.0, -1.0),
+            Vector::new(2.0, 0.0, 0.0),
+            Vector::new(0.0, 0.0, 3.0),
+            material(),
+        );
+        let origin = Point::new(0.5, 0.0, 0.0);
+
+        for _ in 0..100 {
+            let direction = quad.random_direction(origin, 0.0);
+            let ray = Ray::new(origin, direction, None);
+            let hit = quad
+                .hit(&ray, &forward())
+                .expect("expected to hit the quad");
+
+            let distance = hit.get_t() * direction.get_length();
+            let cosine = direction.unit().get_point().1;
+            let expected = distance * distance / (cosine * 6.0);
+
+            assert!((quad.pdf_value(&ray) - expected).abs() < 1e-9 * expected);
+        }
+
+        let away = Ray::new(origin, Vector::new(0.0, -1.0, 0.0), None);
+        assert_eq!(quad.pdf_value(&away), 0.0);
+    }
 }
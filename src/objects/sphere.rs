@@ -1,13 +1,19 @@
-use std::sync::Arc;
 use crate::{
-    bvh::aabb::{merge_aabb, Aabb},
+    bvh::aabb::{Aabb, merge_aabb},
     materials::Materials,
     ray::Ray,
-    utils::{constants::PI, interval::Interval},
-    vector::{dot_product, Point, Vector},
+    scene_file::writer::{Placement, SceneWriter, format_vector},
+    utils::{
+        constants::{F_INF, PI},
+        functions::random_double,
+        interval::Interval,
+        onb::Onb,
+    },
+    vector::{Point, Vector, dot_product, get_random_unit_vector},
 };
+use std::sync::Arc;
 
-use super::hittable::{HitRecord, Hittable};
+use super::hittable::{HitRecord, Hittable, HittableList};
 
 #[derive(Clone)]
 pub struct Sphere {
@@ -41,7 +47,7 @@ impl Sphere {
         radius: f64,
         material: Materials,
     ) -> Sphere {
-        let centre = Ray::new(start_centre, start_centre.subv(end_centre), None);
+        let centre = Ray::new(start_centre, end_centre.subv(start_centre), None);
 
         let radius_vector = Vector::new(radius, radius, radius);
         let start_aabb = Aabb::new_from_extrema_points(
@@ -61,10 +67,6 @@ impl Sphere {
             bounding_box,
         }
     }
-
-    pub fn get_centre(&self) -> Ray {
-        self.centre
-    }
 }
 
 impl Hittable for Sphere {
@@ -133,6 +135,73 @@ impl Hittable for Sphere {
     fn clone_box(&self) -> Arc<dyn Hittable> {
         Arc::new(self.clone())
     }
+
+    fn write_scene(&self, writer: &mut SceneWriter, placement: &Placement) {
+        let material = writer.add_material(&self.material);
+        let start_centre = self.centre.get_origin();
+        let direction = self.centre.get_direction();
+
+        let centre = if direction.near_zero() {
+            format!("centre={}", format_vector(start_centre))
+        } else {
+            format!(
+                "centre={} centre_end={}",
+                format_vector(start_centre),
+                format_vector(start_centre.addv(direction))
+            )
+        };
+
+        writer.push_object(
+            format!(
+                "sphere {} radius={} material={}",
+                centre, self.radius, material
+            ),
+            placement,
+        );
+    }
+
+    fn collect_lights(&self, lights: &mut HittableList) {
+        if self.material.is_emissive() {
+            lights.add_hittable(self.clone_box());
+        }
+    }
+
+    // NOTE: Directions are picked evenly over the cone the sphere covers as seen from
+    // the ray's origin, or over every direction from inside it
+    fn pdf_value(&self, ray: &Ray) -> f64 {
+        if self.hit(ray, &Interval::new(0.001, F_INF)).is_none() {
+            return 0.0;
+        }
+
+        let centre = self.centre.at(ray.get_time());
+        let distance_squared = centre.subv(ray.get_origin()).get_length_squared();
+        let radius_squared = self.radius * self.radius;
+
+        if distance_squared <= radius_squared {
+            return 1.0 / (4.0 * PI);
+        }
+
+        let cos_theta_max = (1.0 - radius_squared / distance_squared).sqrt();
+
+        1.0 / (2.0 * PI * (1.0 - cos_theta_max))
+    }
+
+    fn random_direction(&self, origin: Point, time: f64) -> Vector {
+        let direction = self.centre.at(time).subv(origin);
+        let distance_squared = direction.get_length_squared();
+        let radius_squared = self.radius * self.radius;
+
+        if distance_squared <= radius_squared {
+            return get_random_unit_vector();
+        }
+
+        let cos_theta_max = (1.0 - radius_squared / distance_squared).sqrt();
+        let z = 1.0 + random_double() * (cos_theta_max - 1.0);
+        let phi = 2.0 * PI * random_double();
+        let sin_theta = (1.0 - z * z).sqrt();
+
+        Onb::new(direction).transform(Vector::new(phi.cos() * sin_theta, phi.sin() * sin_theta, z))
+    }
 }
 
 pub fn get_sphere_coordinates(point: Point) -> (f64, f64) {
@@ -146,3 +215,257 @@ pub fn get_sphere_coordinates(point: Point) -> (f64, f64) {
 
     (u, v)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        materials::lambertian::LambertianMaterial, texture::solid_color::SolidColorTexture,
+    };
+
+    const EPSILON: f64 = 1e-9;
+
+    fn material() -> Materials {
+        Materials::Lambertian(LambertianMaterial::new(Arc::new(
+            SolidColorTexture::new_from_rgb(0.5, 0.5, 0.5),
+        )))
+    }
+
+    fn unit_sphere() -> Sphere {
+        Sphere::new(Point::new(0.0, 0.0, 0.0), 1.0, material())
+    }
+
+    fn ray(origin: (f64, f64, f64), direction: (f64, f64, f64)) -> Ray {
+        Ray::new(
+            Point::new(origin.0, origin.1, origin.2),
+            Vector::new(direction.0, direction.1, direction.2),
+            None,
+        )
+    }
+
+    fn forward() -> Interval {
+        Interval::new(0.001, F_INF)
+    }
+
+    fn assert_close(a: Vector, b: Vector) {
+        assert!(
+            a.subv(b).get_length() < EPSILON,
+            "expected {} to be close to {}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn hit_from_outside_takes_the_near_side() {
+        let hit = unit_sphere()
+            .hit(&ray((0.0, 0.0, 5.0), (0.0, 0.0, -1.0)), &forward())
+            .expect("expected a hit");
+
+        assert!((hit.get_t() - 4.0).abs() < EPSILON);
+        assert_close(hit.get_point(), Point::new(0.0, 0.0, 1.0));
+        assert_close(hit.get_normal(), Vector::new(0.0, 0.0, 1.0));
+        assert!(hit.get_front());
+    }
+
+    #[test]
+    fn t_is_in_units_of_the_direction() {
+        let hit = unit_sphere()
+            .hit(&ray((0.0, 0.0, 5.0), (0.0, 0.0, -4.0)), &forward())
+            .expect("expected a hit");
+
+        assert!((hit.get_t() - 1.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn hit_from_inside_takes_the_far_side_facing_back() {
+        let hit = unit_sphere()
+            .hit(&ray((0.0, 0.0, 0.0), (1.0, 0.0, 0.0)), &forward())
+            .expect("expected a hit");
+
+        assert!((hit.get_t() - 1.0).abs() < EPSILON);
+        assert_close(hit.get_point(), Point::new(1.0, 0.0, 0.0));
+        // NOTE: The normal always faces against the ray, front says which side it was
+        assert_close(hit.get_normal(), Vector::new(-1.0, 0.0, 0.0));
+        assert!(!hit.get_front());
+    }
+
+    #[test]
+    fn near_side_outside_the_interval_falls_back_to_the_far_side() {
+        let hit = unit_sphere()
+            .hit(
+                &ray((0.0, 0.0, 5.0), (0.0, 0.0, -1.0)),
+                &Interval::new(4.5, F_INF),
+            )
+            .expect("expected a hit");
+
+        assert!((hit.get_t() - 6.0).abs() < EPSILON);
+        assert!(!hit.get_front());
+    }
+
+    #[test]
+    fn misses_and_hits_behind_the_ray() {
+        let sphere = unit_sphere();
+
+        assert!(
+            sphere
+                .hit(&ray((0.0, 2.0, 5.0), (0.0, 0.0, -1.0)), &forward())
+                .is_none()
+        );
+        assert!(
+            sphere
+                .hit(&ray((0.0, 0.0, 5.0), (0.0, 0.0, 1.0)), &forward())
+                .is_none()
+        );
+        assert!(
+            sphere
+                .hit(
+                    &ray((0.0, 0.0, 5.0), (0.0, 0.0, -1.0)),
+                    &Interval::new(0.001, 3.0)
+                )
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn grazing_ray_touches_at_one_point() {
+        let sphere = unit_sphere();
+
+        let hit = sphere
+            .hit(&ray((-5.0, 1.0, 0.0), (1.0, 0.0, 0.0)), &forward())
+            .expect("expected the tangent ray to touch");
+        assert!((hit.get_t() - 5.0).abs() < EPSILON);
+        assert_close(hit.get_point(), Point::new(0.0, 1.0, 0.0));
+
+        assert!(
+            sphere
+                .hit(&ray((-5.0, 1.0 + 1e-6, 0.0), (1.0, 0.0, 0.0)), &forward())
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn degenerate_rays_never_hit() {
+        let sphere = unit_sphere();
+
+        assert!(
+            sphere
+                .hit(&ray((0.0, 0.0, 5.0), (0.0, 0.0, 0.0)), &forward())
+                .is_none()
+        );
+        assert!(
+            sphere
+                .hit(&ray((0.0, 0.0, 5.0), (f64::NAN, 0.0, -1.0)), &forward())
+                .is_none()
+        );
+        assert!(
+            sphere
+                .hit(&ray((f64::NAN, 0.0, 5.0), (0.0, 0.0, -1.0)), &forward())
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn moving_sphere_goes_from_start_to_end() {
+        let sphere = Sphere::new_moving_sphere(
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(0.0, 2.0, 0.0),
+            0.5,
+            material(),
+        );
+        let up = |time| {
+            Ray::new(
+                Point::new(0.0, -5.0, 0.0),
+                Vector::new(0.0, 1.0, 0.0),
+                Some(time),
+            )
+        };
+
+        let start = sphere.hit(&up(0.0), &forward()).expect("expected a hit");
+        let end = sphere.hit(&up(1.0), &forward()).expect("expected a hit");
+
+        assert!((start.get_t() - 4.5).abs() < EPSILON);
+        assert!((end.get_t() - 6.5).abs() < EPSILON);
+    }
+
+    #[test]
+    fn bounding_box_covers_the_whole_motion() {
+        let sphere = Sphere::new_moving_sphere(
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(0.0, 2.0, 0.0),
+            0.5,
+            material(),
+        );
+
+        assert_eq!(
+            sphere.get_aabb().get_axis_interval(1).get_min_max(),
+            (-0.5, 2.5)
+        );
+    }
+
+    #[test]
+    fn sphere_coordinates_at_the_poles_and_equator() {
+        let check = |point: (f64, f64, f64), expected: (f64, f64)| {
+            let (u, v) = get_sphere_coordinates(Point::new(point.0, point.1, point.2));
+            assert!(
+                (u - expected.0).abs() < EPSILON && (v - expected.1).abs() < EPSILON,
+                "expected {:?} for {:?}, got ({}, {})",
+                expected,
+                point,
+                u,
+                v
+            );
+        };
+
+        check((0.0, 0.0, -1.0), (0.5, 0.5));
+        check((1.0, 0.0, 0.0), (0.75, 0.5));
+        check((-1.0, 0.0, 0.0), (0.25, 0.5));
+        check((0.0, 0.0, 1.0), (1.0, 0.5));
+
+        // NOTE: u is arbitrary at the poles, only v is pinned down
+        assert!(get_sphere_coordinates(Point::new(0.0, -1.0, 0.0)).1.abs() < EPSILON);
+        assert!((get_sphere_coordinates(Point::new(0.0, 1.0, 0.0)).1 - 1.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn sphere_coordinates_stay_in_the_unit_square() {
+        for i in 0..=36 {
+            for j in 0..=18 {
+                let phi = (i as f64 / 36.0) * 2.0 * PI;
+                let theta = (j as f64 / 18.0) * PI;
+                let point = Point::new(
+                    theta.sin() * phi.cos(),
+                    theta.cos(),
+                    theta.sin() * phi.sin(),
+                );
+
+                let (u, v) = get_sphere_coordinates(point);
+                assert!((0.0..=1.0).contains(&u) && (0.0..=1.0).contains(&v));
+            }
+        }
+    }
+
+    #[test]
+    fn light_samples_land_on_the_sphere_with_matching_density() {
+        crate::utils::functions::seed_rng(1);
+
+        let sphere = Sphere::new(Point::new(0.0, 0.0, -4.0), 2.0, material());
+        let origin = Point::new(0.0, 0.0, 0.0);
+        // NOTE: The sphere covers a cone with cos(theta_max) = sqrt(1 - 4 / 16)
+        let expected = 1.0 / (2.0 * PI * (1.0 - f64::sqrt(0.75)));
+
+        for _ in 0..100 {
+            let ray = Ray::new(origin, sphere.random_direction(origin, 0.0), None);
+
+            assert!(sphere.hit(&ray, &forward()).is_some());
+            assert!((sphere.pdf_value(&ray) - expected).abs() < 1e-9);
+        }
+
+        let away = Ray::new(origin, Vector::new(0.0, 0.0, 1.0), None);
+        assert_eq!(sphere.pdf_value(&away), 0.0);
+
+        // NOTE: From inside, every direction hits
+        let inside = Ray::new(Point::new(0.0, 0.0, -4.0), Vector::new(0.0, 1.0, 0.0), None);
+        assert!((sphere.pdf_value(&inside) - 1.0 / (4.0 * PI)).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,130 @@
+use core::fmt::{Display, Formatter, Result};
+use std::{fs::File, io::BufWriter, path::Path};
+
+use image::{
+    ExtendedColorType, ImageEncoder, ImageError, RgbImage,
+    codecs::pnm::{PnmEncoder, PnmSubtype, SampleEncoding},
+};
+
+use crate::checkpoint::CheckpointError;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    Png,
+    Jpeg,
+    Ppm,
+    Tga,
+    Bmp,
+    Hdr,
+    Exr,
+}
+
+impl OutputFormat {
+    pub fn from_name(name: &str) -> Option<OutputFormat> {
+        match name.to_ascii_lowercase().as_str() {
+            "png" => Some(OutputFormat::Png),
+            "jpg" | "jpeg" => Some(OutputFormat::Jpeg),
+            "ppm" => Some(OutputFormat::Ppm),
+            "tga" => Some(OutputFormat::Tga),
+            "bmp" => Some(OutputFormat::Bmp),
+            "hdr" => Some(OutputFormat::Hdr),
+            "exr" => Some(OutputFormat::Exr),
+            _ => None,
+        }
+    }
+
+    // NOTE: Picks the format from the file extension, e.g. render.jpg -> Jpeg
+    pub fn from_path(path: &str) -> std::result::Result<OutputFormat, RenderError> {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or("");
+
+        OutputFormat::from_name(extension)
+            .ok_or_else(|| RenderError::UnsupportedFormat(path.to_string()))
+    }
+
+    pub fn get_image_format(&self) -> image::ImageFormat {
+        match self {
+            OutputFormat::Png => image::ImageFormat::Png,
+            OutputFormat::Jpeg => image::ImageFormat::Jpeg,
+            OutputFormat::Ppm => image::ImageFormat::Pnm,
+            OutputFormat::Tga => image::ImageFormat::Tga,
+            OutputFormat::Bmp => image::ImageFormat::Bmp,
+            OutputFormat::Hdr => image::ImageFormat::Hdr,
+            OutputFormat::Exr => image::ImageFormat::OpenExr,
+        }
+    }
+
+    // NOTE: These store the linear framebuffer as floats instead of 8-bit colors
+    pub fn is_high_dynamic_range(&self) -> bool {
+        matches!(self, OutputFormat::Hdr | OutputFormat::Exr)
+    }
+}
+
+pub fn save_image(
+    img: &RgbImage,
+    output_path: &str,
+    format: OutputFormat,
+) -> std::result::Result<(), RenderError> {
+    let result = match format {
+        // NOTE: The image crate writes PAM (P7) by default, which most viewers can't open
+        OutputFormat::Ppm => File::create(output_path)
+            .map_err(ImageError::IoError)
+            .and_then(|file| {
+                PnmEncoder::new(BufWriter::new(file))
+                    .with_subtype(PnmSubtype::Pixmap(SampleEncoding::Binary))
+                    .write_image(
+                        img.as_raw(),
+                        img.width(),
+                        img.height(),
+                        ExtendedColorType::Rgb8,
+                    )
+            }),
+        format => img.save_with_format(output_path, format.get_image_format()),
+    };
+
+    result.map_err(|error| RenderError::Save {
+        path: output_path.to_string(),
+        error,
+    })
+}
+
+#[derive(Debug)]
+pub enum RenderError {
+    UnsupportedFormat(String),
+    Save { path: String, error: ImageError },
+    Checkpoint(CheckpointError),
+}
+
+impl From<CheckpointError> for RenderError {
+    fn from(error: CheckpointError) -> Self {
+        RenderError::Checkpoint(error)
+    }
+}
+
+impl Display for RenderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            RenderError::UnsupportedFormat(path) => write!(
+                f,
+                "Cannot tell which image format to use for {} (expected png, jpg, ppm, tga, bmp, hdr or exr)",
+                path
+            ),
+            RenderError::Save { path, error } => {
+                write!(f, "Could not save image to {}: {}", path, error)
+            }
+            RenderError::Checkpoint(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RenderError::Save { error, .. } => Some(error),
+            RenderError::Checkpoint(error) => Some(error),
+            RenderError::UnsupportedFormat(_) => None,
+        }
+    }
+}
@@ -0,0 +1,120 @@
+pub mod cosine;
+pub mod hittable;
+#[allow(clippy::module_inception)]
+pub mod pdf;
+pub mod sphere;
+
+use crate::{
+    materials::{dielectric::DielectricBsdf, metal::ConductorBsdf, principled::PrincipledBsdf},
+    vector::Vector,
+};
+
+use cosine::CosinePdf;
+use pdf::Pdf;
+use sphere::SpherePdf;
+
+// NOTE: The distributions materials scatter with, the ones over the lights borrow the
+// scene and are built by the camera instead
+#[derive(Clone, Copy)]
+pub enum Pdfs {
+    Cosine(CosinePdf),
+    Sphere(SpherePdf),
+    Principled(PrincipledBsdf),
+    Conductor(ConductorBsdf),
+    Dielectric(DielectricBsdf),
+}
+
+impl Pdf for Pdfs {
+    fn get_value(&self, direction: Vector) -> f64 {
+        match self {
+            Pdfs::Cosine(pdf) => pdf.get_value(direction),
+            Pdfs::Sphere(pdf) => pdf.get_value(direction),
+            Pdfs::Principled(pdf) => pdf.get_value(direction),
+            Pdfs::Conductor(pdf) => pdf.get_value(direction),
+            Pdfs::Dielectric(pdf) => pdf.get_value(direction),
+        }
+    }
+
+    fn generate(&self) -> Option<Vector> {
+        match self {
+            Pdfs::Cosine(pdf) => pdf.generate(),
+            Pdfs::Sphere(pdf) => pdf.generate(),
+            Pdfs::Principled(pdf) => pdf.generate(),
+            Pdfs::Conductor(pdf) => pdf.generate(),
+            Pdfs::Dielectric(pdf) => pdf.generate(),
+        }
+    }
+}
+
+// NOTE: Veach's power heuristic (beta = 2), the weight for a sample taken with density
+// pdf when another strategy could have produced it with density other_pdf
+pub fn power_heuristic(pdf: f64, other_pdf: f64) -> f64 {
+    let (a, b) = (pdf * pdf, other_pdf * other_pdf);
+
+    if a + b > 0.0 { a / (a + b) } else { 0.0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use crate::{
+        materials::{Materials, lambertian::LambertianMaterial},
+        objects::{hittable::HittableList, quad::Quad},
+        pdf::hittable::HittablePdf,
+        texture::solid_color::SolidColorTexture,
+        utils::{constants::PI, functions::seed_rng},
+        vector::{Point, dot_product},
+    };
+
+    #[test]
+    fn power_heuristic_weights_add_up_to_one() {
+        for (a, b) in [(1.0, 1.0), (0.3, 2.5), (4.0, 0.0)] {
+            assert!((power_heuristic(a, b) + power_heuristic(b, a) - 1.0).abs() < 1e-12);
+        }
+
+        assert_eq!(power_heuristic(0.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn cosine_directions_face_the_normal() {
+        seed_rng(1);
+
+        let normal = Vector::new(1.0, 2.0, -2.0).unit();
+        let pdf = Pdfs::Cosine(CosinePdf::new(normal));
+
+        for _ in 0..100 {
+            let direction = pdf.generate().unwrap();
+            let cosine = dot_product(direction.unit(), normal);
+
+            assert!(cosine >= 0.0);
+            assert!((pdf.get_value(direction) - cosine / PI).abs() < 1e-9);
+        }
+
+        assert_eq!(pdf.get_value(normal.negate()), 0.0);
+    }
+
+    #[test]
+    fn light_directions_point_at_the_lights() {
+        seed_rng(1);
+
+        let mut lights = HittableList::new();
+        lights.add_hittable(Arc::new(Quad::new(
+            Point::new(-1.0, 2.0, -1.0),
+            Vector::new(2.0, 0.0, 0.0),
+            Vector::new(0.0, 0.0, 2.0),
+            Materials::Lambertian(LambertianMaterial::new(Arc::new(
+                SolidColorTexture::new_from_rgb(1.0, 1.0, 1.0),
+            ))),
+        )));
+
+        let light_pdf = HittablePdf::new(&lights, Point::new(0.0, 0.0, 0.0), 0.0);
+        for _ in 0..100 {
+            let direction = light_pdf.generate().unwrap();
+            assert!(light_pdf.get_value(direction) > 0.0);
+        }
+
+        assert_eq!(light_pdf.get_value(Vector::new(0.0, -1.0, 0.0)), 0.0);
+    }
+}
@@ -0,0 +1,32 @@
+use crate::{
+    pdf::pdf::Pdf,
+    utils::{constants::PI, onb::Onb},
+    vector::{Vector, dot_product, get_random_cosine_direction},
+};
+
+// NOTE: Proportional to the cosine with the normal, which is exactly how a Lambertian
+// surface scatters
+#[derive(Clone, Copy)]
+pub struct CosinePdf {
+    basis: Onb,
+}
+
+impl CosinePdf {
+    pub fn new(normal: Vector) -> CosinePdf {
+        CosinePdf {
+            basis: Onb::new(normal),
+        }
+    }
+}
+
+impl Pdf for CosinePdf {
+    fn get_value(&self, direction: Vector) -> f64 {
+        let cosine = dot_product(direction.unit(), self.basis.get_w());
+
+        f64::max(cosine, 0.0) / PI
+    }
+
+    fn generate(&self) -> Option<Vector> {
+        Some(self.basis.transform(get_random_cosine_direction()))
+    }
+}
@@ -0,0 +1,34 @@
+use crate::{
+    objects::hittable::Hittable,
+    pdf::pdf::Pdf,
+    ray::Ray,
+    vector::{Point, Vector},
+};
+
+// NOTE: Directions from origin towards the objects, mostly used with the scene's lights
+pub struct HittablePdf<'a> {
+    objects: &'a dyn Hittable,
+    origin: Point,
+    time: f64,
+}
+
+impl<'a> HittablePdf<'a> {
+    pub fn new(objects: &'a dyn Hittable, origin: Point, time: f64) -> HittablePdf<'a> {
+        HittablePdf {
+            objects,
+            origin,
+            time,
+        }
+    }
+}
+
+impl Pdf for HittablePdf<'_> {
+    fn get_value(&self, direction: Vector) -> f64 {
+        self.objects
+            .pdf_value(&Ray::new(self.origin, direction, Some(self.time)))
+    }
+
+    fn generate(&self) -> Option<Vector> {
+        Some(self.objects.random_direction(self.origin, self.time))
+    }
+}
@@ -0,0 +1,10 @@
+use crate::vector::Vector;
+
+// NOTE: A distribution over directions, densities are per unit solid angle
+pub trait Pdf {
+    fn get_value(&self, direction: Vector) -> f64;
+
+    // NOTE: None when the sample is lost, e.g. a reflection off a microfacet that ends
+    // up below the surface. Those count as scattering nothing.
+    fn generate(&self) -> Option<Vector>;
+}
@@ -0,0 +1,19 @@
+use crate::{
+    pdf::pdf::Pdf,
+    utils::constants::PI,
+    vector::{Vector, get_random_unit_vector},
+};
+
+// NOTE: Every direction equally likely
+#[derive(Clone, Copy)]
+pub struct SpherePdf;
+
+impl Pdf for SpherePdf {
+    fn get_value(&self, _direction: Vector) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+
+    fn generate(&self) -> Option<Vector> {
+        Some(get_random_unit_vector())
+    }
+}
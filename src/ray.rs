@@ -1,4 +1,4 @@
-use crate::vector::{Color, Point, Vector};
+use crate::vector::{Point, Vector};
 
 #[derive(Clone, Copy)]
 pub struct Ray {
@@ -39,15 +39,3 @@ impl Ray {
         self.origin.addv(self.direction.scale(t))
     }
 }
-
-pub fn blue_gradient_vertical(ray: Ray) -> Color {
-    let unit_direction = ray.get_direction().unit();
-    let (_, y, _) = unit_direction.get_point();
-
-    let a = 0.5 * (y + 1.0);
-
-    let blue = Color::new(0.5, 0.7, 1.0);
-    let white = Color::new(1.0, 1.0, 1.0);
-
-    white.scale(1.0 - a).addv(blue.scale(a))
-}
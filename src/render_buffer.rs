@@ -0,0 +1,269 @@
+// NOTE: The result of a render, kept in memory so it can be inspected, post-processed
+// or shown somewhere before (or instead of) being written to a file
+
+use image::{Rgb, Rgb32FImage, RgbImage};
+
+use crate::{
+    output::{OutputFormat, RenderError, save_image},
+    tone_mapping::ToneMapping,
+    vector::Color,
+};
+
+#[derive(Clone)]
+pub struct RenderBuffer {
+    width: u32,
+    height: u32,
+
+    // NOTE: Linear colors (no gamma or clamping) in row-major order, so HDR formats
+    // get the full range of the lights
+    pixels: Vec<Color>,
+}
+
+impl RenderBuffer {
+    pub fn from_pixels(width: u32, height: u32, pixels: Vec<Color>) -> RenderBuffer {
+        assert_eq!(
+            pixels.len(),
+            (width * height) as usize,
+            "RenderBuffer needs exactly width * height pixels"
+        );
+
+        RenderBuffer {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    // NOTE: x is the column and y the row, with (0, 0) at the top left
+    pub fn get_pixel(&self, x: u32, y: u32) -> Color {
+        self.pixels[self.index(x, y)]
+    }
+
+    pub fn to_rgb_image(&self, tone_mapping: &ToneMapping) -> RgbImage {
+        RgbImage::from_fn(self.width, self.height, |x, y| {
+            Rgb(tone_mapping.quantize(self.get_pixel(x, y)))
+        })
+    }
+
+    pub fn to_rgb32f_image(&self) -> Rgb32FImage {
+        Rgb32FImage::from_fn(self.width, self.height, |x, y| {
+            let (r, g, b) = self.get_pixel(x, y).get_point();
+            Rgb([r as f32, g as f32, b as f32])
+        })
+    }
+
+    // NOTE: HDR formats keep the raw linear values, tone mapping is left to whatever
+    // opens them
+    pub fn save(
+        &self,
+        output_path: &str,
+        format: OutputFormat,
+        tone_mapping: &ToneMapping,
+    ) -> Result<(), RenderError> {
+        if format.is_high_dynamic_range() {
+            self.to_rgb32f_image()
+                .save_with_format(output_path, format.get_image_format())
+                .map_err(|error| RenderError::Save {
+                    path: output_path.to_string(),
+                    error,
+                })
+        } else {
+            save_image(&self.to_rgb_image(tone_mapping), output_path, format)
+        }
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        (y * self.width + x) as usize
+    }
+}
+
+// NOTE: Running totals of every sample taken so far, so a render can be built up over
+// several passes and turned into a RenderBuffer at any point along the way
+#[derive(Clone)]
+pub struct AccumulationBuffer {
+    width: u32,
+    height: u32,
+    sums: Vec<Color>,
+    // NOTE: Sum of the squared luminance of every sample, for the variance
+    luminance_square_sums: Vec<f64>,
+    sample_counts: Vec<u32>,
+}
+
+impl AccumulationBuffer {
+    pub fn new(width: u32, height: u32) -> AccumulationBuffer {
+        AccumulationBuffer {
+            width,
+            height,
+            sums: vec![Color::new(0.0, 0.0, 0.0); (width * height) as usize],
+            luminance_square_sums: vec![0.0; (width * height) as usize],
+            sample_counts: vec![0; (width * height) as usize],
+        }
+    }
+
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn from_parts(
+        width: u32,
+        height: u32,
+        sums: Vec<Color>,
+        luminance_square_sums: Vec<f64>,
+        sample_counts: Vec<u32>,
+    ) -> AccumulationBuffer {
+        assert_eq!(
+            sums.len(),
+            (width * height) as usize,
+            "AccumulationBuffer needs exactly width * height sums"
+        );
+        assert_eq!(
+            luminance_square_sums.len(),
+            (width * height) as usize,
+            "AccumulationBuffer needs exactly width * height luminance square sums"
+        );
+        assert_eq!(
+            sample_counts.len(),
+            (width * height) as usize,
+            "AccumulationBuffer needs exactly width * height sample counts"
+        );
+
+        AccumulationBuffer {
+            width,
+            height,
+            sums,
+            luminance_square_sums,
+            sample_counts,
+        }
+    }
+
+    // NOTE: Returns (sample count, sum of luminance, sum of squared luminance)
+    pub fn get_luminance_stats(&self, x: u32, y: u32) -> (u32, f64, f64) {
+        let index = self.index(x, y);
+
+        (
+            self.sample_counts[index],
+            self.sums[index].luminance(),
+            self.luminance_square_sums[index],
+        )
+    }
+
+    pub fn get_total_samples(&self) -> u64 {
+        self.sample_counts.iter().map(|&count| count as u64).sum()
+    }
+
+    pub fn get_sums(&self) -> &[Color] {
+        &self.sums
+    }
+
+    pub fn get_luminance_square_sums(&self) -> &[f64] {
+        &self.luminance_square_sums
+    }
+
+    pub fn get_sample_counts(&self) -> &[u32] {
+        &self.sample_counts
+    }
+
+    // NOTE: Tiles along the right and bottom edges are cut short to fit the image
+    pub fn get_tiles(&self, tile_size: u32) -> Vec<Tile> {
+        let mut tiles = vec![];
+
+        for y in (0..self.height).step_by(tile_size as usize) {
+            for x in (0..self.width).step_by(tile_size as usize) {
+                tiles.push(Tile::new(
+                    x,
+                    y,
+                    tile_size.min(self.width - x),
+                    tile_size.min(self.height - y),
+                ));
+            }
+        }
+
+        tiles
+    }
+
+    pub fn add_tile(&mut self, tile: &Tile) {
+        for row in 0..tile.height {
+            for column in 0..tile.width {
+                let index = self.index(tile.x + column, tile.y + row);
+                let tile_index = (row * tile.width + column) as usize;
+
+                self.sums[index] = self.sums[index].addv(tile.sums[tile_index]);
+                self.luminance_square_sums[index] += tile.luminance_square_sums[tile_index];
+                self.sample_counts[index] += tile.sample_counts[tile_index];
+            }
+        }
+    }
+
+    // NOTE: Pixels without any samples yet come out black
+    pub fn to_render_buffer(&self) -> RenderBuffer {
+        let pixels = self
+            .sums
+            .iter()
+            .zip(&self.sample_counts)
+            .map(|(sum, &count)| {
+                if count == 0 {
+                    Color::new(0.0, 0.0, 0.0)
+                } else {
+                    sum.scale(1.0 / count as f64)
+                }
+            })
+            .collect();
+
+        RenderBuffer::from_pixels(self.width, self.height, pixels)
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        (y * self.width + x) as usize
+    }
+}
+
+// NOTE: A rectangle of the image with its own samples, so threads can render separate
+// tiles without sharing anything until they are added into the accumulation buffer
+pub struct Tile {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    sums: Vec<Color>,
+    // NOTE: Sum of the squared luminance of every sample, for the variance
+    luminance_square_sums: Vec<f64>,
+    sample_counts: Vec<u32>,
+}
+
+impl Tile {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Tile {
+        Tile {
+            x,
+            y,
+            width,
+            height,
+            sums: vec![Color::new(0.0, 0.0, 0.0); (width * height) as usize],
+            luminance_square_sums: vec![0.0; (width * height) as usize],
+            sample_counts: vec![0; (width * height) as usize],
+        }
+    }
+
+    // NOTE: Returns (x, y, width, height), with (x, y) the top left pixel in the image
+    pub fn get_bounds(&self) -> (u32, u32, u32, u32) {
+        (self.x, self.y, self.width, self.height)
+    }
+
+    // NOTE: x and y are in image coordinates, not relative to the tile
+    pub fn add_samples(
+        &mut self,
+        x: u32,
+        y: u32,
+        sum: Color,
+        luminance_square_sum: f64,
+        samples: u32,
+    ) {
+        let index = ((y - self.y) * self.width + (x - self.x)) as usize;
+        self.sums[index] = self.sums[index].addv(sum);
+        self.luminance_square_sums[index] += luminance_square_sum;
+        self.sample_counts[index] += samples;
+    }
+}
@@ -0,0 +1,130 @@
+pub mod halton;
+pub mod independent;
+pub mod sampler;
+pub mod sobol;
+pub mod stratified;
+
+use halton::HaltonSampler;
+use independent::IndependentSampler;
+use sampler::Sampler;
+use sobol::SobolSampler;
+use stratified::StratifiedSampler;
+
+// NOTE: Which sampler the camera hands out for each pixel
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SamplerKind {
+    Independent,
+    Stratified,
+    Halton,
+    Sobol,
+}
+
+impl SamplerKind {
+    pub fn from_name(name: &str) -> Option<SamplerKind> {
+        match name.to_ascii_lowercase().as_str() {
+            "random" | "independent" => Some(SamplerKind::Independent),
+            "stratified" => Some(SamplerKind::Stratified),
+            "halton" => Some(SamplerKind::Halton),
+            "sobol" => Some(SamplerKind::Sobol),
+            _ => None,
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            SamplerKind::Independent => "random",
+            SamplerKind::Stratified => "stratified",
+            SamplerKind::Halton => "halton",
+            SamplerKind::Sobol => "sobol",
+        }
+    }
+}
+
+pub enum Samplers {
+    Independent(IndependentSampler),
+    Stratified(StratifiedSampler),
+    Halton(HaltonSampler),
+    Sobol(SobolSampler),
+}
+
+impl Samplers {
+    // NOTE: A sampler for one pixel, each pixel gets its own scramble so neighbouring
+    // pixels don't repeat the same pattern. Given the same scramble it's the same
+    // sequence, so a pixel can pick it up again on a later pass.
+    pub fn new(kind: SamplerKind, samples_per_pixel: u32, scramble: u64) -> Samplers {
+        match kind {
+            SamplerKind::Independent => Samplers::Independent(IndependentSampler::new()),
+            SamplerKind::Stratified => {
+                Samplers::Stratified(StratifiedSampler::new(samples_per_pixel, scramble))
+            }
+            SamplerKind::Halton => Samplers::Halton(HaltonSampler::new(scramble)),
+            SamplerKind::Sobol => Samplers::Sobol(SobolSampler::new(scramble)),
+        }
+    }
+}
+
+impl Sampler for Samplers {
+    fn start_sample(&mut self, sample_index: u32) {
+        match self {
+            Samplers::Independent(sampler) => sampler.start_sample(sample_index),
+            Samplers::Stratified(sampler) => sampler.start_sample(sample_index),
+            Samplers::Halton(sampler) => sampler.start_sample(sample_index),
+            Samplers::Sobol(sampler) => sampler.start_sample(sample_index),
+        }
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        match self {
+            Samplers::Independent(sampler) => sampler.get_1d(),
+            Samplers::Stratified(sampler) => sampler.get_1d(),
+            Samplers::Halton(sampler) => sampler.get_1d(),
+            Samplers::Sobol(sampler) => sampler.get_1d(),
+        }
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        match self {
+            Samplers::Independent(sampler) => sampler.get_2d(),
+            Samplers::Stratified(sampler) => sampler.get_2d(),
+            Samplers::Halton(sampler) => sampler.get_2d(),
+            Samplers::Sobol(sampler) => sampler.get_2d(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::functions::seed_rng;
+
+    fn draw(sampler: &mut Samplers, sample_index: u32) -> (f64, f64, f64) {
+        // NOTE: Stratified jitters within its strata, so that part has to repeat too
+        seed_rng(sample_index as u64);
+        sampler.start_sample(sample_index);
+
+        let (x, y) = sampler.get_2d();
+        (x, y, sampler.get_1d())
+    }
+
+    // NOTE: Each progressive pass builds the pixel's sampler again, which has to carry
+    // on the same sequence rather than start a differently scrambled one
+    #[test]
+    fn a_rebuilt_sampler_carries_on_the_same_sequence() {
+        for kind in [
+            SamplerKind::Stratified,
+            SamplerKind::Halton,
+            SamplerKind::Sobol,
+        ] {
+            let mut first_pass = Samplers::new(kind, 16, 42);
+            let first: Vec<_> = (0..16).map(|index| draw(&mut first_pass, index)).collect();
+
+            let mut second_pass = Samplers::new(kind, 16, 42);
+            for index in 8..16 {
+                assert_eq!(draw(&mut second_pass, index), first[index as usize]);
+            }
+
+            let mut other_pixel = Samplers::new(kind, 16, 43);
+            assert_ne!(draw(&mut other_pixel, 0), first[0], "{:?}", kind);
+        }
+    }
+}
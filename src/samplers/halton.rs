@@ -0,0 +1,77 @@
+use crate::{
+    samplers::sampler::Sampler,
+    utils::functions::{mix_seed, random_double},
+};
+
+// NOTE: One prime base per dimension: pixel offset, lens and time
+const PRIMES: [u32; 5] = [2, 3, 5, 7, 11];
+
+// NOTE: The Halton sequence, shifted by a random amount per pixel and dimension
+// (a Cranley-Patterson rotation) so every pixel doesn't use the exact same points.
+// The shifts come from the scramble, so the same pixel always gets the same ones.
+// Dimensions past the primes above fall back to plain random numbers.
+pub struct HaltonSampler {
+    sample_index: u32,
+    dimension: usize,
+    offsets: [f64; 5],
+}
+
+impl HaltonSampler {
+    pub fn new(scramble: u64) -> HaltonSampler {
+        let mut offsets = [0.0; 5];
+        for (dimension, offset) in offsets.iter_mut().enumerate() {
+            // NOTE: The top 53 bits, as many as an f64 can hold
+            *offset = (mix_seed(&[scramble, dimension as u64]) >> 11) as f64 / (1u64 << 53) as f64;
+        }
+
+        HaltonSampler {
+            sample_index: 0,
+            dimension: 0,
+            offsets,
+        }
+    }
+
+    fn next(&mut self) -> f64 {
+        let dimension = self.dimension;
+        self.dimension += 1;
+
+        match PRIMES.get(dimension) {
+            Some(&base) => {
+                let value = radical_inverse(base, self.sample_index) + self.offsets[dimension];
+                if value >= 1.0 { value - 1.0 } else { value }
+            }
+            None => random_double(),
+        }
+    }
+}
+
+impl Sampler for HaltonSampler {
+    fn start_sample(&mut self, sample_index: u32) {
+        self.sample_index = sample_index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        self.next()
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        (self.next(), self.next())
+    }
+}
+
+// NOTE: Mirrors the digits of index in the given base around the decimal point,
+// e.g. 6 = 110 in base 2 becomes 0.011 = 0.375
+pub fn radical_inverse(base: u32, mut index: u32) -> f64 {
+    let inverse_base = 1.0 / base as f64;
+    let mut scale = inverse_base;
+    let mut result = 0.0;
+
+    while index > 0 {
+        result += (index % base) as f64 * scale;
+        index /= base;
+        scale *= inverse_base;
+    }
+
+    result
+}
@@ -0,0 +1,22 @@
+use crate::{samplers::sampler::Sampler, utils::functions::random_double};
+
+// NOTE: Every value drawn on its own, as the books do
+pub struct IndependentSampler;
+
+impl IndependentSampler {
+    pub fn new() -> IndependentSampler {
+        IndependentSampler
+    }
+}
+
+impl Sampler for IndependentSampler {
+    fn start_sample(&mut self, _sample_index: u32) {}
+
+    fn get_1d(&mut self) -> f64 {
+        random_double()
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        (random_double(), random_double())
+    }
+}
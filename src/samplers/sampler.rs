@@ -0,0 +1,24 @@
+use crate::utils::functions::mix_seed;
+
+// NOTE: Hands out the random numbers used to set up a camera ray: the offset within
+// the pixel, the point on the lens and the time, in that order. Each call moves on
+// to the next dimension of the current sample, so a sampler can spread every
+// dimension evenly over all the samples of a pixel instead of drawing them blindly.
+pub trait Sampler {
+    // NOTE: Called before each sample, sample_index counts the pixel's samples so far
+    fn start_sample(&mut self, sample_index: u32);
+
+    // NOTE: Values are in [0, 1)
+    fn get_1d(&mut self) -> f64;
+    fn get_2d(&mut self) -> (f64, f64);
+}
+
+// NOTE: One 32 bit scramble per dimension out of the pixel's 64 bit one
+pub fn scrambles<const N: usize>(scramble: u64) -> [u32; N] {
+    let mut scrambles = [0; N];
+    for (dimension, value) in scrambles.iter_mut().enumerate() {
+        *value = (mix_seed(&[scramble, dimension as u64]) >> 32) as u32;
+    }
+
+    scrambles
+}
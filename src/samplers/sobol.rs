@@ -0,0 +1,102 @@
+use std::sync::LazyLock;
+
+use crate::{
+    samplers::sampler::{Sampler, scrambles},
+    utils::functions::random_double,
+};
+
+// NOTE: Primitive polynomials (degree s, coefficients a) and initial direction numbers
+// m for the Sobol dimensions after the first, from Joe and Kuo's new-joe-kuo-6.21201
+const POLYNOMIALS: [(u32, u32, [u32; 3]); 4] = [
+    (1, 0, [1, 0, 0]),
+    (2, 1, [1, 3, 0]),
+    (3, 1, [1, 3, 1]),
+    (3, 2, [1, 1, 1]),
+];
+
+static DIRECTIONS: LazyLock<[[u32; 32]; 5]> = LazyLock::new(|| {
+    let mut directions = [[0; 32]; 5];
+
+    // NOTE: The first dimension is the van der Corput sequence
+    for (bit, direction) in directions[0].iter_mut().enumerate() {
+        *direction = 1 << (31 - bit);
+    }
+
+    for (dimension, &(s, a, m)) in POLYNOMIALS.iter().enumerate() {
+        let v = &mut directions[dimension + 1];
+        let s = s as usize;
+
+        for k in 0..s {
+            v[k] = m[k] << (31 - k);
+        }
+
+        for k in s..32 {
+            v[k] = v[k - s] ^ (v[k - s] >> s);
+
+            for l in 1..s {
+                if (a >> (s - 1 - l)) & 1 == 1 {
+                    v[k] ^= v[k - l];
+                }
+            }
+        }
+    }
+
+    directions
+});
+
+// NOTE: The Sobol sequence XORed with a scramble per pixel and dimension, worked out
+// from the pixel's scramble so the same pixel always gets the same ones.
+// Dimensions past the ones above fall back to plain random numbers.
+pub struct SobolSampler {
+    sample_index: u32,
+    dimension: usize,
+    scrambles: [u32; 5],
+}
+
+impl SobolSampler {
+    pub fn new(scramble: u64) -> SobolSampler {
+        SobolSampler {
+            sample_index: 0,
+            dimension: 0,
+            scrambles: scrambles(scramble),
+        }
+    }
+
+    fn next(&mut self) -> f64 {
+        let dimension = self.dimension;
+        self.dimension += 1;
+
+        if dimension >= DIRECTIONS.len() {
+            return random_double();
+        }
+
+        let mut value = self.scrambles[dimension];
+        let mut index = self.sample_index;
+        let mut bit = 0;
+
+        while index > 0 {
+            if index & 1 == 1 {
+                value ^= DIRECTIONS[dimension][bit];
+            }
+            index >>= 1;
+            bit += 1;
+        }
+
+        value as f64 / 4294967296.0
+    }
+}
+
+impl Sampler for SobolSampler {
+    fn start_sample(&mut self, sample_index: u32) {
+        self.sample_index = sample_index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        self.next()
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        (self.next(), self.next())
+    }
+}
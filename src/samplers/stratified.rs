@@ -0,0 +1,108 @@
+use crate::{
+    samplers::sampler::{Sampler, scrambles},
+    utils::functions::random_double,
+};
+
+// NOTE: Splits each dimension into as many strata as there are samples per pixel (a
+// grid for 2D values) and puts one jittered sample in each. The strata are visited in
+// a shuffled order that differs per dimension, so the pixel offset and the lens sample
+// don't end up tied to each other and any run of samples stays spread out.
+pub struct StratifiedSampler {
+    samples_per_pixel: u32,
+    grid_size: u32,
+    sample_index: u32,
+    dimension: usize,
+    // NOTE: One shuffle per dimension: pixel offset, lens and time. Dimensions past
+    // these fall back to plain random numbers.
+    scrambles: [u32; 3],
+}
+
+impl StratifiedSampler {
+    pub fn new(samples_per_pixel: u32, scramble: u64) -> StratifiedSampler {
+        StratifiedSampler {
+            samples_per_pixel,
+            grid_size: (samples_per_pixel as f64).sqrt().ceil() as u32,
+            sample_index: 0,
+            dimension: 0,
+            scrambles: scrambles(scramble),
+        }
+    }
+
+    fn next_scramble(&mut self) -> Option<u32> {
+        let scramble = self.scrambles.get(self.dimension).copied();
+        self.dimension += 1;
+
+        scramble
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn start_sample(&mut self, sample_index: u32) {
+        self.sample_index = sample_index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let Some(scramble) = self.next_scramble() else {
+            return random_double();
+        };
+        let strata = self.samples_per_pixel;
+        let stratum = permutation_element(self.sample_index % strata, strata, scramble);
+
+        (stratum as f64 + random_double()) / strata as f64
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        let Some(scramble) = self.next_scramble() else {
+            return (random_double(), random_double());
+        };
+        let strata = self.grid_size * self.grid_size;
+        let stratum = permutation_element(self.sample_index % strata, strata, scramble);
+
+        let x = stratum % self.grid_size;
+        let y = stratum / self.grid_size;
+
+        (
+            (x as f64 + random_double()) / self.grid_size as f64,
+            (y as f64 + random_double()) / self.grid_size as f64,
+        )
+    }
+}
+
+// NOTE: Element i of a random permutation of 0..length picked by seed, without having
+// to store the permutation (Kensler, "Correlated Multi-Jittered Sampling")
+pub fn permutation_element(mut i: u32, length: u32, seed: u32) -> u32 {
+    let mut w = length - 1;
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+
+    loop {
+        i ^= seed;
+        i = i.wrapping_mul(0xe170893d);
+        i ^= seed >> 16;
+        i ^= (i & w) >> 4;
+        i ^= seed >> 8;
+        i = i.wrapping_mul(0x0929eb3f);
+        i ^= seed >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | seed >> 27);
+        i = i.wrapping_mul(0x6935fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dcb303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e501cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860a3df);
+        i &= w;
+        i ^= i >> 5;
+
+        if i < length {
+            break;
+        }
+    }
+
+    ((i as u64 + seed as u64) % length as u64) as u32
+}
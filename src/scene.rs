@@ -8,6 +8,83 @@ pub mod earth_scene;
 pub mod perlin_earth_scene;
 pub mod perlin_scene;
 pub mod quad_scene;
+#[allow(clippy::module_inception)]
 pub mod scene;
 pub mod week_render;
 pub mod weekend_render;
+
+use scene::Scene;
+
+pub struct SceneEntry {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub build: fn() -> Scene,
+}
+
+// NOTE: Every built-in scene that can be selected by name from the command line
+pub const SCENES: &[SceneEntry] = &[
+    SceneEntry {
+        name: "basic",
+        description: "Triple spheres of different materials + air bubble within glass sphere",
+        build: basic_scene::basic_scene,
+    },
+    SceneEntry {
+        name: "camera_fov",
+        description: "Two touching spheres for testing the camera FOV",
+        build: camera_fov_scene::camera_fov_scene,
+    },
+    SceneEntry {
+        name: "weekend",
+        description: "Final render from Ray Tracing in One Weekend",
+        build: weekend_render::weekend_scene,
+    },
+    SceneEntry {
+        name: "checker",
+        description: "Weekend final render with a checker textured ground",
+        build: checker_ground_scene::checker_scene,
+    },
+    SceneEntry {
+        name: "earth",
+        description: "Single sphere with the earth image texture",
+        build: earth_scene::earth_scene,
+    },
+    SceneEntry {
+        name: "perlin",
+        description: "Perlin noise ground + marble centre sphere",
+        build: perlin_scene::perlin_scene,
+    },
+    SceneEntry {
+        name: "quad",
+        description: "Five coloured quads (a box whose sides are not connected)",
+        build: quad_scene::quad_scene,
+    },
+    SceneEntry {
+        name: "perlin_earth",
+        description: "Perlin noise ground + earth textured centre sphere",
+        build: perlin_earth_scene::perlin_earth_scene,
+    },
+    SceneEntry {
+        name: "simple_light",
+        description: "Perlin spheres lit by a diffuse light",
+        build: basic_light_scene::simple_light_scene,
+    },
+    SceneEntry {
+        name: "cornell_box",
+        description: "Cornell box with two rotated boxes",
+        build: cornell_box_scene::cornell_box_scene,
+    },
+    SceneEntry {
+        name: "cornell_smoke",
+        description: "Cornell box with smoke and fog boxes",
+        build: cornell_smoke_scene::cornell_smoke_scene,
+    },
+    SceneEntry {
+        name: "week",
+        description: "Final render from Ray Tracing: The Next Week",
+        build: week_render::week_scene,
+    },
+];
+
+pub fn find_scene(name: &str) -> Option<&'static SceneEntry> {
+    SCENES.iter().find(|entry| entry.name == name)
+}
@@ -3,19 +3,14 @@
 use std::sync::Arc;
 
 use crate::{
-    camera::Camera,
-    materials::{
-        Materials, dielectric::DielectricMaterial, diffuse_light::DiffuseLightMaterial,
-        lambertian::LambertianMaterial, metal::MetalMaterial,
-    },
+    camera::CameraBuilder,
+    materials::{Materials, diffuse_light::DiffuseLightMaterial, lambertian::LambertianMaterial},
     objects::{hittable::HittableList, quad::Quad, sphere::Sphere},
     scene::scene::Scene,
     texture::{
         perlin_noise::{PerlinNoiseEffect, PerlinNoiseTexture},
         solid_color::SolidColorTexture,
     },
-    transformation::rotation::Rotation,
-    utils::functions::random_double_in_range,
     vector::{Color, Point, Vector},
 };
 
@@ -32,7 +27,7 @@ pub fn simple_light_scene() -> Scene {
 
     let ground: Sphere = Sphere::new(Point::new(0.0, -1000.0, 0.0), 1000.0, ground_material);
     let centre: Sphere = Sphere::new(Point::new(0.0, 2.0, 0.0), 2.0, centre_material);
-    let side_light_source: Quad = Quad::new(
+    let _side_light_source: Quad = Quad::new(
         Point::new(3.0, 1.0, -2.0),
         Vector::new(2.0, 0.0, 0.0),
         Vector::new(0.0, 2.0, 0.0),
@@ -47,18 +42,17 @@ pub fn simple_light_scene() -> Scene {
     // hittable_list.add_hittable(Arc::new(side_light_source));
     hittable_list.add_hittable(Arc::new(top_light_source));
 
-    let mut camera = Camera::default();
     let look_from = Point::new(26.0, 3.0, 6.0);
     let look_at = Point::new(0.0, 2.0, 0.0);
 
-    camera = camera.override_camera_pos(
-        look_from,
-        look_at,
-        Vector::new(0.0, 1.0, 0.0),
-        20.0,
-        0.0,
-        look_from.subv(look_at).get_length(),
-    );
-    camera.set_background(Color::new(0.0, 0.0, 0.0));
+    let camera = CameraBuilder::new()
+        .look_from(look_from)
+        .look_at(look_at)
+        .vertical_fov(20.0)
+        .focus_dist(look_from.subv(look_at).get_length())
+        .background(Color::new(0.0, 0.0, 0.0))
+        .build()
+        .unwrap();
+
     Scene::new(hittable_list, camera)
 }
@@ -9,7 +9,7 @@ use crate::{
     scene::scene::Scene,
     texture::solid_color::SolidColorTexture,
     utils::constants::PI,
-    vector::{Point, Vector},
+    vector::Point,
 };
 
 pub fn camera_fov_scene() -> Scene {
@@ -4,7 +4,7 @@
 use std::sync::Arc;
 
 use crate::{
-    camera::Camera,
+    camera::CameraBuilder,
     materials::{
         Materials, dielectric::DielectricMaterial, lambertian::LambertianMaterial,
         metal::MetalMaterial,
@@ -13,7 +13,7 @@ use crate::{
     scene::scene::Scene,
     texture::{checker::CheckerTexture, solid_color::SolidColorTexture},
     utils::functions::{random_double, random_double_in_range},
-    vector::{Color, Point, Vector, get_random_unit_vector},
+    vector::{Color, Point, get_random_unit_vector},
 };
 
 pub fn checker_scene() -> Scene {
@@ -49,9 +49,9 @@ pub fn checker_scene() -> Scene {
                     hittable_list.add_hittable(Arc::new(sphere));
                 } else if rdm_mat < 0.85 {
                     let albedo = get_random_unit_vector();
-                    let fuzz = random_double_in_range(0.0, 0.5);
+                    let roughness = random_double_in_range(0.0, 0.5);
 
-                    let mat = Materials::Metal(MetalMaterial::new(albedo, fuzz));
+                    let mat = Materials::Metal(MetalMaterial::new(albedo, roughness));
                     let sphere = Sphere::new(centre, 0.2, mat);
                     hittable_list.add_hittable(Arc::new(sphere));
                 } else {
@@ -77,17 +77,18 @@ pub fn checker_scene() -> Scene {
     let sphere3 = Sphere::new(Point::new(4.0, 1.0, 0.0), 1.0, mat3);
     hittable_list.add_hittable(Arc::new(sphere3));
 
-    let mut camera: Camera = Camera::default();
-    camera = camera.override_sampling_specs(250, 50);
-    camera = camera.override_camera_pos(
-        Point::new(13.0, 2.0, 3.0),
-        Point::new(0.0, 0.0, 0.0),
-        Vector::new(0.0, 1.0, 0.0),
-        20.0,
-        0.6,
-        10.0,
-    );
-    camera = camera.override_image_specs(16.0 / 9.0, 800);
+    let camera = CameraBuilder::new()
+        .aspect_ratio(16.0 / 9.0)
+        .image_width(800)
+        .look_from(Point::new(13.0, 2.0, 3.0))
+        .look_at(Point::new(0.0, 0.0, 0.0))
+        .vertical_fov(20.0)
+        .defocus_angle(0.6)
+        .focus_dist(10.0)
+        .samples_per_pixel(250)
+        .max_depth(50)
+        .build()
+        .unwrap();
 
     Scene::new(hittable_list, camera)
 }
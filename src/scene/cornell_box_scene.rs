@@ -3,12 +3,11 @@
 use std::sync::Arc;
 
 use crate::{
-    camera::{self, Camera},
+    camera::CameraBuilder,
     materials::{Materials, diffuse_light::DiffuseLightMaterial, lambertian::LambertianMaterial},
     objects::{cube::Cube, hittable::HittableList, quad::Quad},
     scene::scene::Scene,
     texture::solid_color::SolidColorTexture,
-    transformation::{rotation::Rotation, translation::Translation},
     vector::{Color, Point, Vector},
 };
 
@@ -98,21 +97,21 @@ pub fn cornell_box_scene() -> Scene {
     hittable_list.add_hittable(Arc::new(left));
     hittable_list.add_hittable(Arc::new(right));
     hittable_list.add_hittable(Arc::new(light_source));
-    hittable_list.add_hittable_list(box_1.to_hittable_list());
-    hittable_list.add_hittable_list(box_2.to_hittable_list());
+    hittable_list.add_hittable_list(box_1.into_hittable_list());
+    hittable_list.add_hittable_list(box_2.into_hittable_list());
 
-    let mut camera = Camera::default();
-    camera = camera.override_image_specs(1.0, 600);
-    camera = camera.override_camera_pos(
-        Point::new(278.0, 278.0, -800.0),
-        Point::new(278.0, 278.0, 0.0),
-        Vector::new(0.0, 1.0, 0.0),
-        40.0,
-        0.0,
-        2.0,
-    );
-    camera = camera.override_sampling_specs(200, 50);
-    camera.set_background(Color::new(0.0, 0.0, 0.0));
+    let camera = CameraBuilder::new()
+        .aspect_ratio(1.0)
+        .image_width(600)
+        .look_from(Point::new(278.0, 278.0, -800.0))
+        .look_at(Point::new(278.0, 278.0, 0.0))
+        .vertical_fov(40.0)
+        .focus_dist(2.0)
+        .samples_per_pixel(200)
+        .max_depth(50)
+        .background(Color::new(0.0, 0.0, 0.0))
+        .build()
+        .unwrap();
 
     Scene::new(hittable_list, camera)
 }
@@ -3,7 +3,7 @@
 use std::sync::Arc;
 
 use crate::{
-    camera::{self, Camera},
+    camera::CameraBuilder,
     materials::{
         Materials, diffuse_light::DiffuseLightMaterial, isotropic::IsotropicMaterial,
         lambertian::LambertianMaterial,
@@ -11,7 +11,6 @@ use crate::{
     objects::{constant_medium::ConstantMedium, cube::Cube, hittable::HittableList, quad::Quad},
     scene::scene::Scene,
     texture::solid_color::SolidColorTexture,
-    transformation::{rotation::Rotation, translation::Translation},
     vector::{Color, Point, Vector},
 };
 
@@ -78,14 +77,14 @@ pub fn cornell_smoke_scene() -> Scene {
     //     Point::new(430.0, 330.0, 460.0),
     //     smoke_material.clone(),
     // )
-    // .to_hittable_list();
+    // .into_hittable_list();
     // let smoke_box = ConstantMedium::new(Arc::new(box_1), 0.01, smoke_material.clone());
     // let box_2 = Cube::new(
     //     Point::new(130.0, 0.0, 65.0),
     //     Point::new(295.0, 165.0, 230.0),
     //     fog_material.clone(),
     // )
-    // .to_hittable_list();
+    // .into_hittable_list();
     // let fog_box = ConstantMedium::new(Arc::new(box_2), 0.01, fog_material.clone());
 
     // NOTE: Final, rotated and translated boxes
@@ -97,7 +96,7 @@ pub fn cornell_smoke_scene() -> Scene {
     box_1.rotate(0.0, 15.0, 0.0);
     box_1.translate(Vector::new(265.0, 0.0, 295.0));
     let smoke_box = ConstantMedium::new(
-        Arc::new(box_1.to_hittable_list()),
+        Arc::new(box_1.into_hittable_list()),
         0.01,
         smoke_material.clone(),
     );
@@ -109,7 +108,7 @@ pub fn cornell_smoke_scene() -> Scene {
     box_2.rotate(0.0, -18.0, 0.0);
     box_2.translate(Vector::new(130.0, 0.0, 65.0));
     let fog_box = ConstantMedium::new(
-        Arc::new(box_2.to_hittable_list()),
+        Arc::new(box_2.into_hittable_list()),
         0.01,
         fog_material.clone(),
     );
@@ -124,18 +123,18 @@ pub fn cornell_smoke_scene() -> Scene {
     hittable_list.add_hittable(Arc::new(smoke_box));
     hittable_list.add_hittable(Arc::new(fog_box));
 
-    let mut camera = Camera::default();
-    camera = camera.override_image_specs(1.0, 600);
-    camera = camera.override_camera_pos(
-        Point::new(278.0, 278.0, -800.0),
-        Point::new(278.0, 278.0, 0.0),
-        Vector::new(0.0, 1.0, 0.0),
-        40.0,
-        0.0,
-        2.0,
-    );
-    camera = camera.override_sampling_specs(200, 50);
-    camera.set_background(Color::new(0.0, 0.0, 0.0));
+    let camera = CameraBuilder::new()
+        .aspect_ratio(1.0)
+        .image_width(600)
+        .look_from(Point::new(278.0, 278.0, -800.0))
+        .look_at(Point::new(278.0, 278.0, 0.0))
+        .vertical_fov(40.0)
+        .focus_dist(2.0)
+        .samples_per_pixel(200)
+        .max_depth(50)
+        .background(Color::new(0.0, 0.0, 0.0))
+        .build()
+        .unwrap();
 
     Scene::new(hittable_list, camera)
 }
@@ -3,20 +3,15 @@
 use std::sync::Arc;
 
 use crate::{
-    camera::Camera,
-    materials::{
-        Materials, dielectric::DielectricMaterial, lambertian::LambertianMaterial,
-        metal::MetalMaterial,
-    },
+    camera::CameraBuilder,
+    materials::{Materials, lambertian::LambertianMaterial},
     objects::{hittable::HittableList, sphere::Sphere},
     scene::scene::Scene,
     texture::{
         image::ImageTexture,
         perlin_noise::{PerlinNoiseEffect, PerlinNoiseTexture},
-        solid_color::SolidColorTexture,
     },
-    utils::functions::random_double_in_range,
-    vector::{Point, Vector},
+    vector::Point,
 };
 
 pub fn perlin_earth_scene() -> Scene {
@@ -34,14 +29,12 @@ pub fn perlin_earth_scene() -> Scene {
     hittable_list.add_hittable(Arc::new(ground));
     hittable_list.add_hittable(Arc::new(centre));
 
-    let mut camera = Camera::default();
-    camera = camera.override_camera_pos(
-        Point::new(13.0, 8.0, 8.0),
-        Point::new(0.0, 2.0, 0.0),
-        Vector::new(0.0, 1.0, 0.0),
-        20.0,
-        0.0,
-        2.0,
-    );
+    let camera = CameraBuilder::new()
+        .look_from(Point::new(13.0, 8.0, 8.0))
+        .look_at(Point::new(0.0, 2.0, 0.0))
+        .vertical_fov(20.0)
+        .focus_dist(2.0)
+        .build()
+        .unwrap();
     Scene::new(hittable_list, camera)
 }
@@ -3,9 +3,9 @@
 use std::sync::Arc;
 
 use crate::{
-    camera::Camera,
+    camera::CameraBuilder,
     materials::{Materials, lambertian::LambertianMaterial},
-    objects::{hittable::HittableList, quad::Quad, sphere::Sphere},
+    objects::{hittable::HittableList, quad::Quad},
     scene::scene::Scene,
     texture::solid_color::SolidColorTexture,
     vector::{Point, Vector},
@@ -69,17 +69,17 @@ pub fn quad_scene() -> Scene {
     let look_from = Point::new(0.0, 0.0, 9.0);
     let look_at = Point::new(0.0, 0.0, 0.0);
 
-    let mut camera = Camera::default();
-    camera = camera.override_image_specs(1.0, 400);
-    camera = camera.override_sampling_specs(100, 50);
-    camera = camera.override_camera_pos(
-        look_from,
-        look_at,
-        Vector::new(0.0, 1.0, 0.0),
-        80.0,
-        0.0,
-        look_from.subv(look_at).get_length(),
-    );
+    let camera = CameraBuilder::new()
+        .aspect_ratio(1.0)
+        .image_width(400)
+        .look_from(look_from)
+        .look_at(look_at)
+        .vertical_fov(80.0)
+        .focus_dist(look_from.subv(look_at).get_length())
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .build()
+        .unwrap();
 
     Scene::new(hittable_list, camera)
 }
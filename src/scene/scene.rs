@@ -1,19 +1,75 @@
-use crate::{bvh::bvh::BvhNode, camera::Camera, objects::hittable::HittableList};
+use crate::{
+    bvh::bvh::BvhNode,
+    camera::Camera,
+    objects::hittable::{Hittable, HittableList},
+    output::{OutputFormat, RenderError},
+    render_buffer::{AccumulationBuffer, RenderBuffer},
+};
 
 pub struct Scene {
     hittable_list: HittableList,
-    camera: Camera
+    camera: Camera,
 }
 
 impl Scene {
     pub fn new(hittable_list: HittableList, camera: Camera) -> Scene {
-        Scene { hittable_list, camera }
+        Scene {
+            hittable_list,
+            camera,
+        }
     }
 
-    pub fn render(&self) {
+    pub fn get_hittable_list(&self) -> &HittableList {
+        &self.hittable_list
+    }
+
+    pub fn get_camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    pub fn set_camera(&mut self, camera: Camera) {
+        self.camera = camera;
+    }
+
+    pub fn render(&self, output_path: &str, format: OutputFormat) -> Result<(), RenderError> {
+        self.camera
+            .render(self.build_bvh(), self.get_lights(), output_path, format)
+    }
+
+    pub fn resume_progressive<F>(
+        &self,
+        accumulator: AccumulationBuffer,
+        samples_done: u32,
+        samples_per_pass: u32,
+        render_seed: u64,
+        on_pass: F,
+    ) -> RenderBuffer
+    where
+        F: FnMut(&AccumulationBuffer, u32),
+    {
+        self.camera.resume_progressive(
+            &self.build_bvh(),
+            &self.get_lights(),
+            accumulator,
+            samples_done,
+            samples_per_pass,
+            render_seed,
+            on_pass,
+        )
+    }
+
+    // NOTE: Every emissive object in the scene, for sampling the lights directly
+    pub fn get_lights(&self) -> HittableList {
+        let mut lights = HittableList::new();
+        self.hittable_list.collect_lights(&mut lights);
+
+        lights
+    }
+
+    fn build_bvh(&self) -> BvhNode {
         let size = self.hittable_list.get_num_hittables();
         let mut hittables = self.hittable_list.clone().get_hittables();
 
-        self.camera.render(BvhNode::new(&mut hittables, 0 as usize, size));
+        BvhNode::new(&mut hittables, 0, size)
     }
 }
@@ -3,24 +3,19 @@
 use std::sync::Arc;
 
 use crate::{
-    camera::Camera,
+    camera::CameraBuilder,
     materials::{
         Materials, dielectric::DielectricMaterial, diffuse_light::DiffuseLightMaterial,
         lambertian::LambertianMaterial, metal::MetalMaterial,
     },
-    objects::{
-        cube::Cube,
-        hittable::{Hittable, HittableList},
-        quad::Quad,
-        sphere::Sphere,
-    },
+    objects::{cube::Cube, hittable::HittableList, quad::Quad, sphere::Sphere},
     scene::scene::Scene,
     texture::{
         image::ImageTexture,
         perlin_noise::{PerlinNoiseEffect, PerlinNoiseTexture},
         solid_color::SolidColorTexture,
     },
-    transformation::{rotation::Rotation, translation::Translation},
+    transformation::rotation::Rotation,
     utils::functions::random_double_in_range,
     vector::{Color, Point, Vector},
 };
@@ -63,7 +58,7 @@ pub fn week_scene() -> Scene {
                 Point::new(x1, y1, z1),
                 ground_material.clone(),
             );
-            hittable_list.add_hittable_list(ground_box.to_hittable_list());
+            hittable_list.add_hittable_list(ground_box.into_hittable_list());
         }
     }
 
@@ -105,7 +100,7 @@ pub fn week_scene() -> Scene {
     // NOTE: Same thing here...
     // Somehow the parameters given in the book don't work?
     let mut floating_spheres_list: HittableList = HittableList::new();
-    for i in 0..1000 {
+    for _ in 0..1000 {
         let location = Vector::new(
             random_double_in_range(-300.0, -165.0),
             random_double_in_range(270.0, 435.0),
@@ -119,20 +114,20 @@ pub fn week_scene() -> Scene {
     hittable_list.add_hittable(Arc::new(rotated_spheres));
 
     // Camera Settings
-    let mut camera = Camera::default();
-    camera = camera.override_image_specs(1.0, 800);
-    camera = camera.override_camera_pos(
-        Point::new(478.0, 278.0, -600.0),
-        Point::new(278.0, 250.0, 0.0),
-        Vector::new(0.0, 1.0, 0.0),
-        40.0,
-        0.0,
-        2.0,
-    );
-    // FIX: Change sampling size and max_depth back to 10000 and 40
-    // (set to 250 and 40 for debugging to speed up)
-    camera = camera.override_sampling_specs(10000, 40);
-    camera.set_background(Color::new(0.0, 0.0, 0.0));
+    let camera = CameraBuilder::new()
+        .aspect_ratio(1.0)
+        .image_width(800)
+        .look_from(Point::new(478.0, 278.0, -600.0))
+        .look_at(Point::new(278.0, 250.0, 0.0))
+        .vertical_fov(40.0)
+        .focus_dist(2.0)
+        // FIX: Change sampling size and max_depth back to 10000 and 40
+        // (set to 250 and 40 for debugging to speed up)
+        .samples_per_pixel(10000)
+        .max_depth(40)
+        .background(Color::new(0.0, 0.0, 0.0))
+        .build()
+        .unwrap();
 
     Scene::new(hittable_list, camera)
 }
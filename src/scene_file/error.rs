@@ -0,0 +1,26 @@
+use core::fmt::{Display, Formatter, Result};
+
+#[derive(Debug)]
+pub enum SceneFileError {
+    Io { path: String, error: std::io::Error },
+    Parse { line: usize, message: String },
+}
+
+impl SceneFileError {
+    pub fn at_line(line: usize, message: String) -> SceneFileError {
+        SceneFileError::Parse { line, message }
+    }
+}
+
+impl Display for SceneFileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            SceneFileError::Io { path, error } => {
+                write!(f, "Could not read scene file {}: {}", path, error)
+            }
+            SceneFileError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for SceneFileError {}
@@ -0,0 +1,917 @@
+// NOTE: Scene description files, so scenes can be tweaked without recompiling
+//
+// One statement per line. Blank lines and anything after a '#' are ignored.
+// Every statement is a keyword followed by `key=value` parameters, where vectors
+// and colors are written as `x,y,z` and numbers may be written as ratios (`16/9`):
+//
+//   camera width=600 aspect_ratio=1 samples_per_pixel=200 max_depth=50
+//   camera look_from=278,278,-800 look_at=278,278,0 vertical_fov=40 background=0,0,0
+//   camera noise_threshold=t [min_samples=16]
+//   camera sampler=random|stratified|halton|sobol
+//   camera integrator=naive|path [roulette_depth=3]
+//   camera seed=n                  (ignored when a seed is passed in, e.g. --seed)
+//   camera auto_focus=true|false
+//   camera tone_map=none|reinhard|aces exposure=stops transfer=gamma2|srgb
+//
+//   texture <name> solid color=r,g,b
+//   texture <name> checker even=<texture> odd=<texture> scale=s
+//   texture <name> image path=<file>   (relative to the scene file)
+//   texture <name> perlin scale=s [depth=7] [effect=marble|white_noise]
+//
+//   material <name> lambertian texture=<texture> | color=r,g,b
+//   material <name> metal albedo=r,g,b | conductor=gold|copper|aluminium | eta=r,g,b k=r,g,b
+//       [roughness=r|<texture>]
+//   material <name> dielectric refraction_index=n [roughness=r|<texture>]
+//       [absorption=r,g,b [absorption_distance=d]]
+//   material <name> isotropic texture=<texture> | color=r,g,b
+//   material <name> diffuse_light texture=<texture> | color=r,g,b
+//   material <name> principled texture=<texture> | color=r,g,b [metallic=m] [roughness=r]
+//       [specular=s] [sheen=s] [clearcoat=c] [clearcoat_gloss=g] [transmission=t]
+//       [refraction_index=n]
+//
+//   sphere centre=x,y,z [centre_end=x,y,z] radius=r material=<material>
+//   quad q=x,y,z u=x,y,z v=x,y,z material=<material>
+//   cube min=x,y,z max=x,y,z material=<material>
+//   medium boundary=<object> density=d material=<material>
+//   translate object=<object> offset=x,y,z
+//   rotate object=<object> y=degrees
+//
+// Objects are added straight into the world, unless they are given a `name=<name>`
+// (kept aside so `medium`, `translate` and `rotate` can refer to them) or a
+// `group=<name>` (appended to a named list of objects, referred to the same way).
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    camera::CameraBuilder,
+    integrators::IntegratorKind,
+    materials::{
+        Materials,
+        dielectric::DielectricMaterial,
+        diffuse_light::DiffuseLightMaterial,
+        isotropic::IsotropicMaterial,
+        lambertian::LambertianMaterial,
+        metal::{Conductor, MetalMaterial},
+        principled::PrincipledMaterial,
+    },
+    objects::{
+        constant_medium::ConstantMedium,
+        cube::Cube,
+        hittable::{Hittable, HittableList},
+        quad::Quad,
+        sphere::Sphere,
+    },
+    samplers::SamplerKind,
+    scene::scene::Scene,
+    scene_file::error::SceneFileError,
+    texture::{
+        checker::CheckerTexture,
+        image::ImageTexture,
+        perlin_noise::{PerlinNoiseEffect, PerlinNoiseTexture},
+        solid_color::SolidColorTexture,
+        texture::Texture,
+    },
+    tone_mapping::{ToneMapOperator, ToneMapping, TransferCurve},
+    transformation::{rotation::Rotation, translation::Translation},
+    utils::functions::seed_rng,
+    vector::Vector,
+};
+
+// NOTE: A seed given here takes the place of the file's own, so the command line's
+// --seed decides everything random in the scene too
+pub fn load_scene_file(path: &str, seed: Option<u64>) -> Result<Scene, SceneFileError> {
+    let source = fs::read_to_string(path).map_err(|error| SceneFileError::Io {
+        path: path.to_string(),
+        error,
+    })?;
+    let base_dir = Path::new(path).parent().unwrap_or(Path::new(""));
+
+    parse_scene_in(&source, seed, base_dir)
+}
+
+// NOTE: Without a file to go by, image paths are relative to the working directory.
+// Only the tests have scenes that aren't in a file.
+#[cfg(test)]
+pub fn parse_scene(source: &str, seed: Option<u64>) -> Result<Scene, SceneFileError> {
+    parse_scene_in(source, seed, Path::new(""))
+}
+
+// NOTE: Image paths in the source are relative to base_dir
+fn parse_scene_in(
+    source: &str,
+    seed: Option<u64>,
+    base_dir: &Path,
+) -> Result<Scene, SceneFileError> {
+    let mut parser = SceneParser::new(seed, base_dir);
+
+    for (index, raw_line) in source.lines().enumerate() {
+        let content = match raw_line.split_once('#') {
+            Some((content, _comment)) => content,
+            None => raw_line,
+        };
+
+        if let Some(mut statement) = Statement::parse(index + 1, content)? {
+            parser.parse_statement(&mut statement)?;
+            statement.finish()?;
+        }
+    }
+
+    // NOTE: Only checked once the whole file is read, since a camera split over a few
+    // statements can be invalid halfway through
+    let camera = parser
+        .camera
+        .build()
+        .map_err(|err| SceneFileError::at_line(parser.camera_line, err.to_string()))?;
+
+    Ok(Scene::new(parser.world, camera))
+}
+
+struct Statement<'a> {
+    line: usize,
+    keyword: &'a str,
+    positional: Vec<&'a str>,
+    params: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Statement<'a> {
+    fn parse(line: usize, content: &'a str) -> Result<Option<Statement<'a>>, SceneFileError> {
+        let mut tokens = content.split_whitespace();
+
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => return Ok(None),
+        };
+
+        let mut positional = vec![];
+        let mut params: Vec<(&str, &str)> = vec![];
+
+        for token in tokens {
+            match token.split_once('=') {
+                Some((key, value)) => {
+                    if key.is_empty() || value.is_empty() {
+                        return Err(SceneFileError::at_line(
+                            line,
+                            format!("Malformed parameter '{}'", token),
+                        ));
+                    }
+                    if params.iter().any(|(existing, _)| *existing == key) {
+                        return Err(SceneFileError::at_line(
+                            line,
+                            format!("Parameter '{}' given more than once", key),
+                        ));
+                    }
+
+                    params.push((key, value));
+                }
+                None => {
+                    if !params.is_empty() {
+                        return Err(SceneFileError::at_line(
+                            line,
+                            format!("Expected key=value, got '{}'", token),
+                        ));
+                    }
+
+                    positional.push(token);
+                }
+            }
+        }
+
+        Ok(Some(Statement {
+            line,
+            keyword,
+            positional,
+            params,
+        }))
+    }
+
+    fn error(&self, message: String) -> SceneFileError {
+        SceneFileError::at_line(self.line, message)
+    }
+
+    fn positional(&self, index: usize, what: &str) -> Result<&'a str, SceneFileError> {
+        self.positional
+            .get(index)
+            .copied()
+            .ok_or_else(|| self.error(format!("'{}' is missing its {}", self.keyword, what)))
+    }
+
+    fn take(&mut self, key: &str) -> Option<&'a str> {
+        let index = self.params.iter().position(|(k, _)| *k == key)?;
+        Some(self.params.remove(index).1)
+    }
+
+    fn take_required(&mut self, key: &str) -> Result<&'a str, SceneFileError> {
+        self.take(key).ok_or_else(|| {
+            self.error(format!(
+                "'{}' is missing the required parameter '{}'",
+                self.keyword, key
+            ))
+        })
+    }
+
+    fn take_f64(&mut self, key: &str) -> Result<Option<f64>, SceneFileError> {
+        match self.take(key) {
+            Some(value) => self.parse_number(key, value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn take_required_f64(&mut self, key: &str) -> Result<f64, SceneFileError> {
+        let value = self.take_required(key)?;
+        self.parse_number(key, value)
+    }
+
+    fn take_u32(&mut self, key: &str) -> Result<Option<u32>, SceneFileError> {
+        match self.take(key) {
+            Some(value) => match value.parse::<u32>() {
+                Ok(parsed) if parsed > 0 => Ok(Some(parsed)),
+                _ => Err(self.error(format!(
+                    "Expected a positive integer for '{}', got '{}'",
+                    key, value
+                ))),
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn take_vector(&mut self, key: &str) -> Result<Option<Vector>, SceneFileError> {
+        match self.take(key) {
+            Some(value) => self.parse_vector(key, value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn take_required_vector(&mut self, key: &str) -> Result<Vector, SceneFileError> {
+        let value = self.take_required(key)?;
+        self.parse_vector(key, value)
+    }
+
+    fn parse_number(&self, key: &str, value: &str) -> Result<f64, SceneFileError> {
+        let number = match value.split_once('/') {
+            Some((numerator, denominator)) => {
+                match (numerator.parse::<f64>(), denominator.parse::<f64>()) {
+                    (Ok(numerator), Ok(denominator)) if denominator != 0.0 => {
+                        Some(numerator / denominator)
+                    }
+                    _ => None,
+                }
+            }
+            None => value.parse::<f64>().ok(),
+        };
+
+        match number {
+            Some(number) if number.is_finite() => Ok(number),
+            _ => Err(self.error(format!("Expected a number for '{}', got '{}'", key, value))),
+        }
+    }
+
+    fn parse_vector(&self, key: &str, value: &str) -> Result<Vector, SceneFileError> {
+        let components: Vec<&str> = value.split(',').collect();
+        if components.len() != 3 {
+            return Err(self.error(format!(
+                "Expected three comma separated numbers (x,y,z) for '{}', got '{}'",
+                key, value
+            )));
+        }
+
+        let x = self.parse_number(key, components[0])?;
+        let y = self.parse_number(key, components[1])?;
+        let z = self.parse_number(key, components[2])?;
+
+        Ok(Vector::new(x, y, z))
+    }
+
+    // NOTE: Anything left over was never asked for, so it's most likely a typo
+    fn finish(&self) -> Result<(), SceneFileError> {
+        match self.params.first() {
+            Some((key, _)) => Err(self.error(format!(
+                "Unknown parameter '{}' for '{}'",
+                key, self.keyword
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+struct SceneParser {
+    camera: CameraBuilder,
+    // NOTE: The last camera statement, which the camera's errors are reported against
+    camera_line: usize,
+    // NOTE: Passed in from outside the file, wins over any camera seed= in it
+    seed: Option<u64>,
+    // NOTE: The directory of the scene file, which image paths are relative to
+    base_dir: PathBuf,
+    textures: HashMap<String, Arc<dyn Texture>>,
+    materials: HashMap<String, Materials>,
+    objects: HashMap<String, Arc<dyn Hittable>>,
+    groups: HashMap<String, HittableList>,
+    world: HittableList,
+}
+
+impl SceneParser {
+    fn new(seed: Option<u64>, base_dir: &Path) -> SceneParser {
+        SceneParser {
+            camera: CameraBuilder::new().seed(seed),
+            camera_line: 0,
+            seed,
+            base_dir: base_dir.to_path_buf(),
+            textures: HashMap::new(),
+            materials: HashMap::new(),
+            objects: HashMap::new(),
+            groups: HashMap::new(),
+            world: HittableList::new(),
+        }
+    }
+
+    fn parse_statement(&mut self, statement: &mut Statement) -> Result<(), SceneFileError> {
+        match statement.keyword {
+            "camera" => self.parse_camera(statement),
+            "texture" => self.parse_texture(statement),
+            "material" => self.parse_material(statement),
+            "sphere" | "quad" | "cube" | "medium" | "translate" | "rotate" => {
+                let hittable = self.parse_object(statement)?;
+                self.place_object(statement, hittable)
+            }
+            keyword => Err(statement.error(format!("Unknown statement '{}'", keyword))),
+        }
+    }
+
+    fn parse_camera(&mut self, statement: &mut Statement) -> Result<(), SceneFileError> {
+        if !statement.positional.is_empty() {
+            return Err(statement.error("'camera' only takes key=value parameters".to_string()));
+        }
+
+        let mut camera = self.camera.clone();
+
+        if let Some(aspect_ratio) = statement.take_f64("aspect_ratio")? {
+            camera = camera.aspect_ratio(aspect_ratio);
+        }
+        if let Some(image_width) = statement.take_u32("width")? {
+            camera = camera.image_width(image_width);
+        }
+        if let Some(samples_per_pixel) = statement.take_u32("samples_per_pixel")? {
+            camera = camera.samples_per_pixel(samples_per_pixel);
+        }
+        if let Some(max_depth) = statement.take_u32("max_depth")? {
+            camera = camera.max_depth(max_depth);
+        }
+        // NOTE: 0 is fine here, roulette then starts from the first bounce
+        if let Some(value) = statement.take("roulette_depth") {
+            let roulette_depth = value.parse::<u32>().map_err(|_| {
+                statement.error(format!(
+                    "Expected a non-negative integer for 'roulette_depth', got '{}'",
+                    value
+                ))
+            })?;
+
+            camera = camera.roulette_depth(roulette_depth);
+        }
+        let (mut min_samples, mut noise_threshold) = camera.get_adaptive_sampling();
+        if let Some(samples) = statement.take_u32("min_samples")? {
+            min_samples = samples;
+        }
+        if let Some(threshold) = statement.take_f64("noise_threshold")? {
+            noise_threshold = threshold;
+        }
+        camera = camera.adaptive_sampling(min_samples, noise_threshold);
+
+        if let Some(name) = statement.take("sampler") {
+            camera = camera.sampler(SamplerKind::from_name(name).ok_or_else(|| {
+                statement.error(format!(
+                    "Unknown sampler '{}', expected random, stratified, halton or sobol",
+                    name
+                ))
+            })?);
+        }
+        if let Some(name) = statement.take("integrator") {
+            camera = camera.integrator(IntegratorKind::from_name(name).ok_or_else(|| {
+                statement.error(format!(
+                    "Unknown integrator '{}', expected naive or path",
+                    name
+                ))
+            })?);
+        }
+
+        // NOTE: Also seeds everything random further down the file (Perlin textures), so
+        // the seed goes on one of the first camera lines
+        if let Some(value) = statement.take("seed") {
+            let seed = value.parse::<u64>().map_err(|_| {
+                statement.error(format!(
+                    "Expected a non-negative integer for 'seed', got '{}'",
+                    value
+                ))
+            })?;
+
+            if self.seed.is_none() {
+                seed_rng(seed);
+                camera = camera.seed(Some(seed));
+            }
+        }
+
+        if let Some(look_from) = statement.take_vector("look_from")? {
+            camera = camera.look_from(look_from);
+        }
+        if let Some(look_at) = statement.take_vector("look_at")? {
+            camera = camera.look_at(look_at);
+        }
+        if let Some(v_up) = statement.take_vector("v_up")? {
+            camera = camera.v_up(v_up);
+        }
+        if let Some(vertical_fov) = statement.take_f64("vertical_fov")? {
+            camera = camera.vertical_fov(vertical_fov);
+        }
+        if let Some(defocus_angle) = statement.take_f64("defocus_angle")? {
+            camera = camera.defocus_angle(defocus_angle);
+        }
+        if let Some(focus_dist) = statement.take_f64("focus_dist")? {
+            camera = camera.focus_dist(focus_dist);
+        }
+        if let Some(background) = statement.take_vector("background")? {
+            camera = camera.background(background);
+        }
+
+        if let Some(value) = statement.take("auto_focus") {
+            camera = camera.auto_focus(match value {
+                "true" => true,
+                "false" => false,
+                value => {
+                    return Err(statement.error(format!(
+                        "Expected true or false for 'auto_focus', got '{}'",
+                        value
+                    )));
+                }
+            });
+        }
+
+        let tone_mapping = camera.get_tone_mapping();
+        let mut operator = tone_mapping.get_operator();
+        let mut exposure = tone_mapping.get_exposure();
+        let mut curve = tone_mapping.get_curve();
+
+        if let Some(name) = statement.take("tone_map") {
+            operator = ToneMapOperator::from_name(name).ok_or_else(|| {
+                statement.error(format!(
+                    "Unknown tone mapping operator '{}', expected none, reinhard or aces",
+                    name
+                ))
+            })?;
+        }
+        if let Some(stops) = statement.take_f64("exposure")? {
+            exposure = stops;
+        }
+        if let Some(name) = statement.take("transfer") {
+            curve = TransferCurve::from_name(name).ok_or_else(|| {
+                statement.error(format!(
+                    "Unknown transfer curve '{}', expected gamma2 or srgb",
+                    name
+                ))
+            })?;
+        }
+
+        self.camera = camera.tone_mapping(ToneMapping::new(operator, exposure, curve));
+        self.camera_line = statement.line;
+
+        Ok(())
+    }
+
+    fn parse_texture(&mut self, statement: &mut Statement) -> Result<(), SceneFileError> {
+        let name = statement.positional(0, "name")?;
+        let kind = statement.positional(1, "type")?;
+
+        if self.textures.contains_key(name) {
+            return Err(statement.error(format!("Texture '{}' is already defined", name)));
+        }
+
+        let texture: Arc<dyn Texture> = match kind {
+            "solid" => Arc::new(SolidColorTexture::new_from_color(
+                statement.take_required_vector("color")?,
+            )),
+            "checker" => {
+                let even = self.lookup_texture(statement, "even")?;
+                let odd = self.lookup_texture(statement, "odd")?;
+                let scale = statement.take_required_f64("scale")?;
+
+                Arc::new(CheckerTexture::new(even, odd, scale))
+            }
+            "image" => {
+                let path = self.base_dir.join(statement.take_required("path")?);
+                Arc::new(
+                    ImageTexture::open(&path.to_string_lossy())
+                        .map_err(|err| statement.error(err))?,
+                )
+            }
+            "perlin" => {
+                let scale = statement.take_required_f64("scale")?;
+                let depth = statement.take_u32("depth")?.unwrap_or(7) as i32;
+                let effect = match statement.take("effect").unwrap_or("marble") {
+                    "marble" => PerlinNoiseEffect::Marble,
+                    "white_noise" => PerlinNoiseEffect::WhiteNoise,
+                    effect => {
+                        return Err(statement.error(format!(
+                            "Unknown perlin effect '{}', expected marble or white_noise",
+                            effect
+                        )));
+                    }
+                };
+
+                Arc::new(PerlinNoiseTexture::new(scale, depth, effect))
+            }
+            kind => return Err(statement.error(format!("Unknown texture type '{}'", kind))),
+        };
+
+        self.textures.insert(name.to_string(), texture);
+        Ok(())
+    }
+
+    fn parse_material(&mut self, statement: &mut Statement) -> Result<(), SceneFileError> {
+        let name = statement.positional(0, "name")?;
+        let kind = statement.positional(1, "type")?;
+
+        if self.materials.contains_key(name) {
+            return Err(statement.error(format!("Material '{}' is already defined", name)));
+        }
+
+        let material = match kind {
+            "lambertian" => {
+                Materials::Lambertian(LambertianMaterial::new(self.texture_or_color(statement)?))
+            }
+            "metal" => Materials::Metal(self.parse_metal(statement)?),
+            "dielectric" => Materials::Dielectric(self.parse_dielectric(statement)?),
+            "isotropic" => {
+                Materials::Isotropic(IsotropicMaterial::new(self.texture_or_color(statement)?))
+            }
+            "diffuse_light" => {
+                Materials::Diffuse(DiffuseLightMaterial::new(self.texture_or_color(statement)?))
+            }
+            "principled" => Materials::Principled(self.parse_principled(statement)?),
+            kind => return Err(statement.error(format!("Unknown material type '{}'", kind))),
+        };
+
+        self.materials.insert(name.to_string(), material);
+        Ok(())
+    }
+
+    fn parse_object(
+        &mut self,
+        statement: &mut Statement,
+    ) -> Result<Arc<dyn Hittable>, SceneFileError> {
+        if !statement.positional.is_empty() {
+            return Err(statement.error(format!(
+                "'{}' only takes key=value parameters",
+                statement.keyword
+            )));
+        }
+
+        let hittable: Arc<dyn Hittable> = match statement.keyword {
+            "sphere" => {
+                let centre = statement.take_required_vector("centre")?;
+                let centre_end = statement.take_vector("centre_end")?;
+                let radius = statement.take_required_f64("radius")?;
+                let material = self.lookup_material(statement)?;
+
+                match centre_end {
+                    Some(centre_end) => Arc::new(Sphere::new_moving_sphere(
+                        centre, centre_end, radius, material,
+                    )),
+                    None => Arc::new(Sphere::new(centre, radius, material)),
+                }
+            }
+            "quad" => {
+                let q = statement.take_required_vector("q")?;
+                let u = statement.take_required_vector("u")?;
+                let v = statement.take_required_vector("v")?;
+                let material = self.lookup_material(statement)?;
+
+                Arc::new(Quad::new(q, u, v, material))
+            }
+            "cube" => {
+                let min = statement.take_required_vector("min")?;
+                let max = statement.take_required_vector("max")?;
+                let material = self.lookup_material(statement)?;
+
+                Arc::new(Cube::new(min, max, material).into_hittable_list())
+            }
+            "medium" => {
+                let boundary = self.lookup_object(statement, "boundary")?;
+                let density = statement.take_required_f64("density")?;
+                let material = self.lookup_material(statement)?;
+
+                if density <= 0.0 {
+                    return Err(statement.error("'density' must be positive".to_string()));
+                }
+
+                Arc::new(ConstantMedium::new(boundary, density, material))
+            }
+            "translate" => {
+                let hittable = self.lookup_object(statement, "object")?;
+                let offset = statement.take_required_vector("offset")?;
+
+                Arc::new(Translation::new(hittable, offset))
+            }
+            "rotate" => {
+                let hittable = self.lookup_object(statement, "object")?;
+                let y_rotation = statement.take_required_f64("y")?;
+
+                Arc::new(Rotation::new(hittable, 0.0, y_rotation, 0.0))
+            }
+            keyword => return Err(statement.error(format!("Unknown object '{}'", keyword))),
+        };
+
+        Ok(hittable)
+    }
+
+    fn place_object(
+        &mut self,
+        statement: &mut Statement,
+        hittable: Arc<dyn Hittable>,
+    ) -> Result<(), SceneFileError> {
+        match (statement.take("name"), statement.take("group")) {
+            (Some(_), Some(_)) => Err(statement.error(
+                "An object can be given either a 'name' or a 'group', not both".to_string(),
+            )),
+            (Some(name), None) => {
+                if self.objects.contains_key(name) || self.groups.contains_key(name) {
+                    return Err(statement.error(format!("Object '{}' is already defined", name)));
+                }
+
+                self.objects.insert(name.to_string(), hittable);
+                Ok(())
+            }
+            (None, Some(group)) => {
+                if self.objects.contains_key(group) {
+                    return Err(statement.error(format!(
+                        "'{}' is already defined as an object, not a group",
+                        group
+                    )));
+                }
+
+                self.groups
+                    .entry(group.to_string())
+                    .or_insert_with(HittableList::new)
+                    .add_hittable(hittable);
+                Ok(())
+            }
+            (None, None) => {
+                self.world.add_hittable(hittable);
+                Ok(())
+            }
+        }
+    }
+
+    fn texture_or_color(
+        &self,
+        statement: &mut Statement,
+    ) -> Result<Arc<dyn Texture>, SceneFileError> {
+        let has_texture = statement.params.iter().any(|(key, _)| *key == "texture");
+        let has_color = statement.params.iter().any(|(key, _)| *key == "color");
+
+        match (has_texture, has_color) {
+            (true, false) => self.lookup_texture(statement, "texture"),
+            (false, true) => Ok(Arc::new(SolidColorTexture::new_from_color(
+                statement.take_required_vector("color")?,
+            ))),
+            _ => Err(statement.error(format!(
+                "'{}' needs exactly one of 'texture' or 'color'",
+                statement.keyword
+            ))),
+        }
+    }
+
+    // NOTE: Every parameter but the refraction index is a number or a texture name
+    fn parse_metal(&self, statement: &mut Statement) -> Result<MetalMaterial, SceneFileError> {
+        let material = if let Some(name) = statement.take("conductor") {
+            let conductor = Conductor::from_name(name).ok_or_else(|| {
+                statement.error(format!(
+                    "Unknown conductor '{}', expected gold, copper or aluminium",
+                    name
+                ))
+            })?;
+
+            MetalMaterial::new_from_conductor(conductor, 0.0)
+        } else if let Some(eta) = statement.take_vector("eta")? {
+            MetalMaterial::new_from_ior(eta, statement.take_required_vector("k")?, 0.0)
+        } else {
+            MetalMaterial::new(statement.take_required_vector("albedo")?, 0.0)
+        };
+
+        // NOTE: Older scene files call the roughness fuzz
+        let roughness = match self.number_or_texture(statement, "roughness")? {
+            Some(roughness) => Some(roughness),
+            None => statement.take_f64("fuzz")?.map(solid_number),
+        };
+
+        Ok(match roughness {
+            Some(roughness) => material.roughness(roughness),
+            None => material,
+        })
+    }
+
+    fn parse_dielectric(
+        &self,
+        statement: &mut Statement,
+    ) -> Result<DielectricMaterial, SceneFileError> {
+        let mut material =
+            DielectricMaterial::new(statement.take_required_f64("refraction_index")?);
+
+        if let Some(roughness) = self.number_or_texture(statement, "roughness")? {
+            material = material.roughness(roughness);
+        }
+
+        let absorption = statement.take_vector("absorption")?;
+        let distance = statement.take_f64("absorption_distance")?;
+        if absorption.is_some() || distance.is_some() {
+            let distance = distance.unwrap_or(1.0);
+            if distance <= 0.0 {
+                return Err(statement.error("'absorption_distance' must be positive".to_string()));
+            }
+
+            material = material.absorption(
+                absorption.unwrap_or_else(|| Vector::new(1.0, 1.0, 1.0)),
+                distance,
+            );
+        }
+
+        Ok(material)
+    }
+
+    fn parse_principled(
+        &self,
+        statement: &mut Statement,
+    ) -> Result<PrincipledMaterial, SceneFileError> {
+        let mut material = PrincipledMaterial::new(self.texture_or_color(statement)?);
+
+        if let Some(metallic) = self.number_or_texture(statement, "metallic")? {
+            material = material.metallic(metallic);
+        }
+        if let Some(roughness) = self.number_or_texture(statement, "roughness")? {
+            material = material.roughness(roughness);
+        }
+        if let Some(specular) = self.number_or_texture(statement, "specular")? {
+            material = material.specular(specular);
+        }
+        if let Some(sheen) = self.number_or_texture(statement, "sheen")? {
+            material = material.sheen(sheen);
+        }
+
+        let clearcoat = self.number_or_texture(statement, "clearcoat")?;
+        let clearcoat_gloss = self.number_or_texture(statement, "clearcoat_gloss")?;
+        if clearcoat.is_some() || clearcoat_gloss.is_some() {
+            material = material.clearcoat(
+                clearcoat.unwrap_or_else(|| solid_number(0.0)),
+                clearcoat_gloss.unwrap_or_else(|| solid_number(1.0)),
+            );
+        }
+
+        let transmission = self.number_or_texture(statement, "transmission")?;
+        let refraction_index = statement.take_f64("refraction_index")?;
+        if transmission.is_some() || refraction_index.is_some() {
+            material = material.transmission(
+                transmission.unwrap_or_else(|| solid_number(0.0)),
+                refraction_index.unwrap_or(1.5),
+            );
+        }
+
+        Ok(material)
+    }
+
+    fn number_or_texture(
+        &self,
+        statement: &mut Statement,
+        key: &str,
+    ) -> Result<Option<Arc<dyn Texture>>, SceneFileError> {
+        let Some(value) = statement.take(key) else {
+            return Ok(None);
+        };
+
+        match self.textures.get(value) {
+            Some(texture) => Ok(Some(texture.clone())),
+            None => Ok(Some(solid_number(statement.parse_number(key, value)?))),
+        }
+    }
+
+    fn lookup_texture(
+        &self,
+        statement: &mut Statement,
+        key: &str,
+    ) -> Result<Arc<dyn Texture>, SceneFileError> {
+        let name = statement.take_required(key)?;
+
+        self.textures
+            .get(name)
+            .cloned()
+            .ok_or_else(|| statement.error(format!("Unknown texture '{}'", name)))
+    }
+
+    fn lookup_material(&self, statement: &mut Statement) -> Result<Materials, SceneFileError> {
+        let name = statement.take_required("material")?;
+
+        self.materials
+            .get(name)
+            .cloned()
+            .ok_or_else(|| statement.error(format!("Unknown material '{}'", name)))
+    }
+
+    fn lookup_object(
+        &self,
+        statement: &mut Statement,
+        key: &str,
+    ) -> Result<Arc<dyn Hittable>, SceneFileError> {
+        let name = statement.take_required(key)?;
+
+        if let Some(hittable) = self.objects.get(name) {
+            return Ok(hittable.clone());
+        }
+
+        match self.groups.get(name) {
+            Some(group) => Ok(Arc::new(group.clone())),
+            None => Err(statement.error(format!("Unknown object '{}'", name))),
+        }
+    }
+}
+
+fn solid_number(value: f64) -> Arc<dyn Texture> {
+    Arc::new(SolidColorTexture::new_from_rgb(value, value, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::functions::random_double;
+
+    // NOTE: earth.scene points at ../texture_assets, which is only right from scenes/
+    #[test]
+    fn image_paths_are_relative_to_the_scene_file() {
+        if let Err(err) = load_scene_file("scenes/earth.scene", None) {
+            panic!("{}", err);
+        }
+
+        let source = fs::read_to_string("scenes/earth.scene").unwrap();
+        assert!(parse_scene(&source, None).is_err());
+    }
+
+    // NOTE: Every error below is on line 4, after a comment and a blank line that
+    // still have to be counted
+    fn assert_error_on_line_4(statement: &str, expected: &str) {
+        let source = format!(
+            "# A scene\n\nmaterial white lambertian color=0.7,0.7,0.7\n{}\n",
+            statement
+        );
+
+        match parse_scene(&source, None).err() {
+            Some(SceneFileError::Parse { line, message }) => {
+                assert_eq!(line, 4, "{}", message);
+                assert!(message.contains(expected), "{}", message);
+            }
+            _ => panic!("'{}' should not parse", statement),
+        }
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_line() {
+        assert_error_on_line_4("cylinder radius=1", "Unknown statement");
+        assert_error_on_line_4(
+            "sphere centre=0,1 radius=1 material=white",
+            "three comma separated numbers",
+        );
+        assert_error_on_line_4(
+            "sphere centre=0,0,0 radius=1 radius=2 material=white",
+            "more than once",
+        );
+        assert_error_on_line_4(
+            "sphere centre=0,0,0 radius=1 material=black",
+            "Unknown material",
+        );
+        assert_error_on_line_4("material red lambertian texture=red", "Unknown texture");
+        assert_error_on_line_4("camera samples_per_pixel=0", "positive integer");
+    }
+
+    #[test]
+    fn a_seed_passed_in_wins_over_the_files() {
+        // NOTE: Seeded the way main does it before loading the file
+        seed_rng(9);
+        let scene = match parse_scene("camera seed=5\n", Some(9)) {
+            Ok(scene) => scene,
+            Err(err) => panic!("{}", err),
+        };
+        let after_parsing = random_double();
+
+        seed_rng(9);
+        assert_eq!(random_double(), after_parsing);
+        assert_eq!(scene.get_camera().get_seed(), Some(9));
+    }
+
+    #[test]
+    fn the_camera_is_checked_against_its_last_line() {
+        let source = "camera width=100\n\ncamera aspect_ratio=-1\n";
+
+        assert!(matches!(
+            parse_scene(source, None).err(),
+            Some(SceneFileError::Parse { line: 3, .. })
+        ));
+    }
+}
@@ -0,0 +1,292 @@
+// NOTE: The reverse of the parser, turns an in-memory Scene back into a scene file
+//
+// Textures and materials are written once and shared by name, everything inside a
+// transform or medium is given a generated name so it can be referred to. Anything
+// built out of other objects (e.g. a Cube) comes out as the objects it is made of.
+
+use std::{collections::HashMap, fs, io, sync::Arc};
+
+use crate::{
+    integrators::{DEFAULT_ROULETTE_DEPTH, IntegratorKind},
+    materials::Materials,
+    objects::hittable::Hittable,
+    samplers::SamplerKind,
+    scene::scene::Scene,
+    texture::texture::Texture,
+    tone_mapping::ToneMapping,
+    vector::Vector,
+};
+
+// NOTE: Where a written object should end up, mirrors the name= and group= parameters
+#[derive(Clone)]
+pub enum Placement {
+    World,
+    Named(String),
+    Group(String),
+}
+
+impl Placement {
+    // NOTE: A name can only be given to one statement, so anything made of several
+    // objects has to collect them into a group of that name instead
+    pub fn for_children(&self) -> Placement {
+        match self {
+            Placement::Named(name) => Placement::Group(name.clone()),
+            placement => placement.clone(),
+        }
+    }
+}
+
+pub struct SceneWriter {
+    textures: Vec<String>,
+    texture_names: HashMap<String, String>,
+    materials: Vec<String>,
+    material_names: HashMap<String, String>,
+    objects: Vec<String>,
+    object_count: usize,
+}
+
+impl SceneWriter {
+    pub fn new() -> SceneWriter {
+        SceneWriter {
+            textures: vec![],
+            texture_names: HashMap::new(),
+            materials: vec![],
+            material_names: HashMap::new(),
+            objects: vec![],
+            object_count: 0,
+        }
+    }
+
+    // NOTE: Identical definitions share a name, so a texture used by many materials
+    // is only written once
+    pub fn add_texture(&mut self, texture: &Arc<dyn Texture>) -> String {
+        let definition = texture.to_scene_definition(self);
+
+        if let Some(name) = self.texture_names.get(&definition) {
+            return name.clone();
+        }
+
+        let name = format!("texture_{}", self.textures.len());
+        self.textures
+            .push(format!("texture {} {}", name, definition));
+        self.texture_names.insert(definition, name.clone());
+
+        name
+    }
+
+    // NOTE: A grey solid colour is written as the plain number the parser turns back
+    // into the same texture, anything else by name
+    pub fn add_number_or_texture(&mut self, texture: &Arc<dyn Texture>) -> String {
+        match texture.get_solid_color().map(|color| color.get_point()) {
+            Some((r, g, b)) if r == g && g == b => r.to_string(),
+            _ => self.add_texture(texture),
+        }
+    }
+
+    // NOTE: For materials that take either a color= or a texture= parameter
+    pub fn add_color_or_texture(&mut self, texture: &Arc<dyn Texture>) -> String {
+        match texture.get_solid_color() {
+            Some(color) => format!("color={}", format_vector(color)),
+            None => format!("texture={}", self.add_texture(texture)),
+        }
+    }
+
+    pub fn add_material(&mut self, material: &Materials) -> String {
+        let definition = material.to_scene_definition(self);
+
+        if let Some(name) = self.material_names.get(&definition) {
+            return name.clone();
+        }
+
+        let name = format!("material_{}", self.materials.len());
+        self.materials
+            .push(format!("material {} {}", name, definition));
+        self.material_names.insert(definition, name.clone());
+
+        name
+    }
+
+    // NOTE: Writes the hittable under a fresh name and returns it, for objects that
+    // wrap another one (translate, rotate, medium)
+    pub fn add_named_object(&mut self, hittable: &dyn Hittable) -> String {
+        self.object_count += 1;
+        let name = format!("object_{}", self.object_count);
+
+        hittable.write_scene(self, &Placement::Named(name.clone()));
+
+        name
+    }
+
+    pub fn push_object(&mut self, statement: String, placement: &Placement) {
+        let line = match placement {
+            Placement::World => statement,
+            Placement::Named(name) => format!("{} name={}", statement, name),
+            Placement::Group(group) => format!("{} group={}", statement, group),
+        };
+
+        self.objects.push(line);
+    }
+
+    pub fn write(&mut self, scene: &Scene) -> String {
+        for hittable in scene.get_hittable_list().iter() {
+            hittable.write_scene(self, &Placement::World);
+        }
+
+        let camera = scene.get_camera();
+        let (aspect_ratio, image_width) = camera.get_image_specs();
+        let (samples_per_pixel, max_depth) = camera.get_sampling_specs();
+        let (look_from, look_at, v_up, vertical_fov, defocus_angle, focus_dist) =
+            camera.get_camera_pos();
+
+        let mut lines = vec![
+            format!(
+                "camera width={} aspect_ratio={} samples_per_pixel={} max_depth={}",
+                image_width, aspect_ratio, samples_per_pixel, max_depth
+            ),
+            format!(
+                "camera look_from={} look_at={} v_up={} vertical_fov={}",
+                format_vector(look_from),
+                format_vector(look_at),
+                format_vector(v_up),
+                vertical_fov
+            ),
+            format!(
+                "camera defocus_angle={} focus_dist={} background={}",
+                defocus_angle,
+                focus_dist,
+                format_vector(camera.get_background())
+            ),
+        ];
+
+        let (min_samples, noise_threshold) = camera.get_adaptive_sampling();
+        if noise_threshold > 0.0 {
+            lines.push(format!(
+                "camera noise_threshold={} min_samples={}",
+                noise_threshold, min_samples
+            ));
+        }
+
+        if camera.get_sampler() != SamplerKind::Independent {
+            lines.push(format!(
+                "camera sampler={}",
+                camera.get_sampler().get_name()
+            ));
+        }
+
+        if camera.get_integrator() != IntegratorKind::Path {
+            lines.push(format!(
+                "camera integrator={}",
+                camera.get_integrator().get_name()
+            ));
+        }
+
+        if camera.get_roulette_depth() != DEFAULT_ROULETTE_DEPTH {
+            lines.push(format!(
+                "camera roulette_depth={}",
+                camera.get_roulette_depth()
+            ));
+        }
+
+        if let Some(seed) = camera.get_seed() {
+            lines.push(format!("camera seed={}", seed));
+        }
+
+        if camera.get_auto_focus() {
+            lines.push("camera auto_focus=true".to_string());
+        }
+
+        // NOTE: Left out when it's the default, so older scene files export unchanged
+        let tone_mapping = camera.get_tone_mapping();
+        if tone_mapping != ToneMapping::default() {
+            lines.push(format!(
+                "camera tone_map={} exposure={} transfer={}",
+                tone_mapping.get_operator().get_name(),
+                tone_mapping.get_exposure(),
+                tone_mapping.get_curve().get_name()
+            ));
+        }
+
+        for section in [&self.textures, &self.materials, &self.objects] {
+            if !section.is_empty() {
+                lines.push(String::new());
+                lines.extend(section.iter().cloned());
+            }
+        }
+
+        lines.push(String::new());
+        lines.join("\n")
+    }
+}
+
+pub fn write_scene(scene: &Scene) -> String {
+    SceneWriter::new().write(scene)
+}
+
+pub fn save_scene_file(scene: &Scene, path: &str) -> io::Result<()> {
+    fs::write(path, write_scene(scene))
+}
+
+// NOTE: Display for f64 prints the shortest string that parses back to the same value,
+// so nothing is lost going through a file
+pub fn format_vector(vector: Vector) -> String {
+    let (x, y, z) = vector.get_point();
+    format!("{},{},{}", x, y, z)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene_file::parser::parse_scene;
+
+    fn rewrite(source: &str) -> String {
+        match parse_scene(source, None) {
+            Ok(scene) => write_scene(&scene),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    // NOTE: Written by the writer in the first place, with shared textures, groups,
+    // transforms and media, so it has to come back out exactly as it went in
+    #[test]
+    fn written_scenes_read_back_unchanged() {
+        let source = include_str!("../../scenes/cornell_smoke.scene");
+
+        assert_eq!(rewrite(source), source);
+    }
+
+    #[test]
+    fn solid_parameters_are_written_inline() {
+        let written = rewrite(
+            "texture dark solid color=0.2,0.2,0.2
+texture light solid color=0.7,0.7,0.7
+texture checks checker even=dark odd=light scale=0.5
+material gold principled color=1,0.78,0.34 metallic=1 roughness=0.08
+material brushed metal albedo=0.8,0.8,0.8 roughness=checks
+sphere centre=0,0,0 radius=1 material=gold
+sphere centre=2,0,0 radius=1 material=brushed
+",
+        );
+
+        assert!(written.contains(
+            "principled color=1,0.78,0.34 metallic=1 roughness=0.08 specular=0.5 sheen=0"
+        ));
+        assert!(written.contains("metal albedo=0.8,0.8,0.8 roughness=texture_2\n"));
+        assert_eq!(written.matches("solid color=").count(), 2, "{}", written);
+    }
+
+    // NOTE: Hand written files lose their comments and names the first time through,
+    // after that they should be stable
+    #[test]
+    fn rewriting_is_stable() {
+        for source in [
+            include_str!("../../scenes/cornell_box.scene"),
+            include_str!("../../scenes/metals.scene"),
+            include_str!("../../scenes/glass.scene"),
+            include_str!("../../scenes/principled.scene"),
+        ] {
+            let written = rewrite(source);
+
+            assert_eq!(rewrite(&written), written);
+        }
+    }
+}
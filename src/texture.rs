@@ -2,4 +2,5 @@ pub mod checker;
 pub mod image;
 pub mod perlin_noise;
 pub mod solid_color;
+#[allow(clippy::module_inception)]
 pub mod texture;
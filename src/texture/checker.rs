@@ -1,4 +1,5 @@
 use crate::{
+    scene_file::writer::SceneWriter,
     texture::{solid_color::SolidColorTexture, texture::Texture},
     vector::{Color, Point},
 };
@@ -7,6 +8,7 @@ use std::{fmt::Display, sync::Arc};
 pub struct CheckerTexture {
     even_texture: Arc<dyn Texture>,
     odd_texture: Arc<dyn Texture>,
+    scale: f64,
     inv_scale: f64,
 }
 
@@ -19,19 +21,17 @@ impl CheckerTexture {
         CheckerTexture {
             even_texture,
             odd_texture,
+            scale,
             inv_scale: 1.0 / scale,
         }
     }
 
     pub fn new_from_solid_color(even_color: Color, odd_color: Color, scale: f64) -> CheckerTexture {
-        let even_texture = Arc::new(SolidColorTexture::new_from_color(even_color));
-        let odd_texture = Arc::new(SolidColorTexture::new_from_color(odd_color));
-
-        CheckerTexture {
-            even_texture,
-            odd_texture,
-            inv_scale: 1.0 / scale,
-        }
+        CheckerTexture::new(
+            Arc::new(SolidColorTexture::new_from_color(even_color)),
+            Arc::new(SolidColorTexture::new_from_color(odd_color)),
+            scale,
+        )
     }
 }
 
@@ -49,6 +49,13 @@ impl Texture for CheckerTexture {
             self.even_texture.get_value(u, v, point)
         }
     }
+
+    fn to_scene_definition(&self, writer: &mut SceneWriter) -> String {
+        let even = writer.add_texture(&self.even_texture);
+        let odd = writer.add_texture(&self.odd_texture);
+
+        format!("checker even={} odd={} scale={}", even, odd, self.scale)
+    }
 }
 
 impl Display for CheckerTexture {
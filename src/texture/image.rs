@@ -1,47 +1,57 @@
 use image::{GenericImageView, ImageReader, RgbImage};
-use std::{fmt::Display, sync::Arc};
+use std::{fmt::Display, path, sync::Arc};
 
 use crate::{
+    scene_file::writer::SceneWriter,
     texture::texture::Texture,
     utils::interval::Interval,
     vector::{Color, Point},
 };
 
 pub struct ImageTexture {
+    image_filepath: String,
     image: Arc<RgbImage>,
     normalised_interval: Interval,
 }
 
 impl ImageTexture {
     pub fn new(image_filepath: &str) -> ImageTexture {
-        let image = match ImageReader::open(image_filepath) {
-            Ok(raw_img) => match raw_img.decode() {
-                Ok(decoded_img) => {
-                    let (width, height): (u32, u32) = decoded_img.dimensions();
-                    if width == 0 || height == 0 {
-                        panic!(
-                            "Invalid image dimensions at {} for image texture",
-                            image_filepath
-                        )
-                    }
-
-                    decoded_img.to_rgb8()
-                }
-                Err(err) => panic!(
-                    "Could not decode image at {} for image texture\n{}",
-                    image_filepath, err
-                ),
-            },
-            Err(err) => panic!(
+        match ImageTexture::open(image_filepath) {
+            Ok(texture) => texture,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    // NOTE: Same as new, but hands the error back instead of panicking
+    // (e.g. for textures named in a scene file)
+    pub fn open(image_filepath: &str) -> Result<ImageTexture, String> {
+        let raw_img = ImageReader::open(image_filepath).map_err(|err| {
+            format!(
                 "Could not open image at {} for image texture\n{}",
                 image_filepath, err
-            ),
-        };
+            )
+        })?;
 
-        ImageTexture {
-            image: Arc::new(image),
-            normalised_interval: Interval::new(0.0, 1.0),
+        let decoded_img = raw_img.decode().map_err(|err| {
+            format!(
+                "Could not decode image at {} for image texture\n{}",
+                image_filepath, err
+            )
+        })?;
+
+        let (width, height): (u32, u32) = decoded_img.dimensions();
+        if width == 0 || height == 0 {
+            return Err(format!(
+                "Invalid image dimensions at {} for image texture",
+                image_filepath
+            ));
         }
+
+        Ok(ImageTexture {
+            image_filepath: image_filepath.to_string(),
+            image: Arc::new(decoded_img.to_rgb8()),
+            normalised_interval: Interval::new(0.0, 1.0),
+        })
     }
 }
 
@@ -64,6 +74,16 @@ impl Texture for ImageTexture {
 
         Color::new(r, g, b)
     }
+
+    // NOTE: Written as an absolute path, since the scene file reading it back looks for
+    // images next to itself and could be saved anywhere
+    fn to_scene_definition(&self, _writer: &mut SceneWriter) -> String {
+        let path = path::absolute(&self.image_filepath)
+            .map(|path| path.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| self.image_filepath.clone());
+
+        format!("image path={}", path)
+    }
 }
 
 impl Display for ImageTexture {
@@ -1,19 +1,13 @@
 // NOTE: Reference Link: https://adrianb.io/2014/08/09/perlinnoise.html
 
 use core::f64;
-use std::{
-    fmt::{Display, write},
-    i64, isize,
-};
-
-use rand::{distr::weighted, seq::SliceRandom, thread_rng};
+use std::fmt::Display;
 
 use crate::{
+    scene_file::writer::SceneWriter,
     texture::texture::Texture,
-    utils::functions::{random_double, random_int_in_range},
-    vector::{
-        Color, Point, Vector, dot_product, get_random_unit_vector, get_random_unit_vector_in_range,
-    },
+    utils::functions::shuffle,
+    vector::{Color, Point, Vector, dot_product, get_random_unit_vector_in_range},
 };
 
 pub enum PerlinNoiseEffect {
@@ -37,7 +31,7 @@ impl PerlinNoiseTexture {
     pub fn new(scale: f64, depth: i32, effect: PerlinNoiseEffect) -> PerlinNoiseTexture {
         let mut random_vector = vec![];
 
-        for i in 0..Self::POINT_COUNT {
+        for _ in 0..Self::POINT_COUNT {
             random_vector.push(get_random_unit_vector_in_range(-1.0, 1.0));
         }
 
@@ -61,7 +55,7 @@ impl PerlinNoiseTexture {
         let mut tmp_point = point;
         let mut weight = 1.0;
 
-        for i in 0..self.depth {
+        for _ in 0..self.depth {
             acc += weight * self.gen_noise(&tmp_point);
             weight *= 0.5;
             tmp_point = tmp_point.scale(2.0);
@@ -104,9 +98,9 @@ impl PerlinNoiseTexture {
 
         let mut acc = 0.0;
 
-        for di in 0..2 {
-            for dj in 0..2 {
-                for dk in 0..2 {
+        for (di, c_i) in c.iter().enumerate() {
+            for (dj, c_ij) in c_i.iter().enumerate() {
+                for (dk, c_ijk) in c_ij.iter().enumerate() {
                     let fdi = di as f64;
                     let fdj = dj as f64;
                     let fdk = dk as f64;
@@ -116,7 +110,7 @@ impl PerlinNoiseTexture {
                     let j = vv * fdj + (1.0 - fdj) * (1.0 - vv);
                     let k = ww * fdk + (1.0 - fdk) * (1.0 - ww);
 
-                    acc += i * j * k * dot_product(c[di][dj][dk], weighted_vec);
+                    acc += i * j * k * dot_product(*c_ijk, weighted_vec);
                 }
             }
         }
@@ -131,7 +125,7 @@ impl PerlinNoiseTexture {
             perm.push(i as u64);
         }
 
-        perm.shuffle(&mut thread_rng());
+        shuffle(&mut perm);
 
         perm
     }
@@ -152,12 +146,24 @@ impl PerlinNoiseTexture {
 }
 
 impl Texture for PerlinNoiseTexture {
-    fn get_value(&self, u: f64, v: f64, point: Point) -> Color {
+    fn get_value(&self, _u: f64, _v: f64, point: Point) -> Color {
         match self.effect {
             PerlinNoiseEffect::Marble => self.simulate_marble_effect(point),
             PerlinNoiseEffect::WhiteNoise => self.simulate_white_noise(point),
         }
     }
+
+    fn to_scene_definition(&self, _writer: &mut SceneWriter) -> String {
+        let effect = match self.effect {
+            PerlinNoiseEffect::Marble => "marble",
+            PerlinNoiseEffect::WhiteNoise => "white_noise",
+        };
+
+        format!(
+            "perlin scale={} depth={} effect={}",
+            self.scale, self.depth, effect
+        )
+    }
 }
 
 impl Display for PerlinNoiseTexture {
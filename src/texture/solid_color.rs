@@ -1,6 +1,7 @@
 use std::fmt::Display;
 
 use crate::{
+    scene_file::writer::{SceneWriter, format_vector},
     texture::texture::Texture,
     vector::{Color, Point},
 };
@@ -22,9 +23,17 @@ impl SolidColorTexture {
 }
 
 impl Texture for SolidColorTexture {
-    fn get_value(&self, u: f64, v: f64, point: Point) -> Color {
+    fn get_value(&self, _u: f64, _v: f64, _point: Point) -> Color {
         self.albedo
     }
+
+    fn to_scene_definition(&self, _writer: &mut SceneWriter) -> String {
+        format!("solid color={}", format_vector(self.albedo))
+    }
+
+    fn get_solid_color(&self) -> Option<Color> {
+        Some(self.albedo)
+    }
 }
 
 impl Display for SolidColorTexture {
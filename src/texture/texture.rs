@@ -1,7 +1,18 @@
-use std::{fmt::Display, sync::Arc};
+use std::fmt::Display;
 
-use crate::vector::{Color, Point};
+use crate::{
+    scene_file::writer::SceneWriter,
+    vector::{Color, Point},
+};
 
 pub trait Texture: Send + Sync + Display {
     fn get_value(&self, u: f64, v: f64, point: Point) -> Color;
+
+    // NOTE: Type and parameters as written after the name in a scene file texture statement
+    fn to_scene_definition(&self, writer: &mut SceneWriter) -> String;
+
+    // NOTE: The colour everywhere, for textures that are the same all over
+    fn get_solid_color(&self) -> Option<Color> {
+        None
+    }
 }
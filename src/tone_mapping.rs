@@ -0,0 +1,163 @@
+// NOTE: Turns the linear colors of a render into displayable 8-bit colors
+//
+// Exposure is applied first (in stops, so +1 doubles the brightness), then the
+// operator squeezes the unbounded range into [0, 1], and finally the transfer curve
+// encodes it for the display. The default (no operator, gamma 2 curve) matches the
+// hard clamp and sqrt the renderer has always used.
+
+use crate::vector::Color;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ToneMapOperator {
+    // NOTE: Anything brighter than 1 is simply clipped
+    Clamp,
+    Reinhard,
+    Aces,
+}
+
+impl ToneMapOperator {
+    pub fn from_name(name: &str) -> Option<ToneMapOperator> {
+        match name.to_ascii_lowercase().as_str() {
+            "none" | "clamp" => Some(ToneMapOperator::Clamp),
+            "reinhard" => Some(ToneMapOperator::Reinhard),
+            "aces" => Some(ToneMapOperator::Aces),
+            _ => None,
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            ToneMapOperator::Clamp => "none",
+            ToneMapOperator::Reinhard => "reinhard",
+            ToneMapOperator::Aces => "aces",
+        }
+    }
+
+    fn apply(&self, color: Color) -> Color {
+        match self {
+            ToneMapOperator::Clamp => color,
+            ToneMapOperator::Reinhard => {
+                // NOTE: Scaling by luminance rather than per channel keeps the hue of
+                // bright lights instead of washing them out to white
+                let luminance = color.luminance();
+
+                if luminance <= 0.0 {
+                    color
+                } else {
+                    color.scale(1.0 / (1.0 + luminance))
+                }
+            }
+            ToneMapOperator::Aces => {
+                // NOTE: Krzysztof Narkowicz's fit of the ACES filmic curve
+                fn aces(x: f64) -> f64 {
+                    (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)
+                }
+
+                let (r, g, b) = color.get_point();
+                Color::new(aces(r.max(0.0)), aces(g.max(0.0)), aces(b.max(0.0)))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransferCurve {
+    // NOTE: The sqrt approximation from the books
+    Gamma2,
+    Srgb,
+}
+
+impl TransferCurve {
+    pub fn from_name(name: &str) -> Option<TransferCurve> {
+        match name.to_ascii_lowercase().as_str() {
+            "gamma2" => Some(TransferCurve::Gamma2),
+            "srgb" => Some(TransferCurve::Srgb),
+            _ => None,
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            TransferCurve::Gamma2 => "gamma2",
+            TransferCurve::Srgb => "srgb",
+        }
+    }
+
+    fn encode(&self, val: f64) -> f64 {
+        if val.is_nan() || val <= 0.0 {
+            return 0.0;
+        }
+
+        match self {
+            TransferCurve::Gamma2 => val.sqrt(),
+            TransferCurve::Srgb => {
+                if val <= 0.0031308 {
+                    12.92 * val
+                } else {
+                    1.055 * val.powf(1.0 / 2.4) - 0.055
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ToneMapping {
+    operator: ToneMapOperator,
+    exposure: f64,
+    curve: TransferCurve,
+}
+
+impl ToneMapping {
+    pub fn new(operator: ToneMapOperator, exposure: f64, curve: TransferCurve) -> ToneMapping {
+        ToneMapping {
+            operator,
+            exposure,
+            curve,
+        }
+    }
+
+    pub fn get_operator(&self) -> ToneMapOperator {
+        self.operator
+    }
+
+    pub fn get_exposure(&self) -> f64 {
+        self.exposure
+    }
+
+    pub fn get_curve(&self) -> TransferCurve {
+        self.curve
+    }
+
+    // NOTE: Linear color in, display encoded color in [0, 1] out
+    pub fn apply(&self, color: Color) -> Color {
+        let exposed = color.scale(f64::powf(2.0, self.exposure));
+        let (r, g, b) = self.operator.apply(exposed).get_point();
+
+        Color::new(
+            self.curve.encode(r).min(1.0),
+            self.curve.encode(g).min(1.0),
+            self.curve.encode(b).min(1.0),
+        )
+    }
+
+    pub fn quantize(&self, color: Color) -> [u8; 3] {
+        // NOTE: The books' quantization, 255.8 so values just under 1 still reach 255
+        fn to_u8(val: f64) -> u8 {
+            if val >= 1.0 { 255 } else { (255.8 * val) as u8 }
+        }
+
+        let (r, g, b) = self.apply(color).get_point();
+        [to_u8(r), to_u8(g), to_u8(b)]
+    }
+}
+
+impl Default for ToneMapping {
+    fn default() -> Self {
+        ToneMapping {
+            operator: ToneMapOperator::Clamp,
+            exposure: 0.0,
+            curve: TransferCurve::Gamma2,
+        }
+    }
+}
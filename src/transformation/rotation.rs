@@ -2,9 +2,10 @@ use std::sync::Arc;
 
 use crate::{
     bvh::aabb::Aabb,
-    objects::hittable::{HitRecord, Hittable},
+    objects::hittable::{HitRecord, Hittable, HittableList},
     ray::Ray,
-    utils::{constants::F_INF, functions::degrees_to_radians, interval::Interval},
+    scene_file::writer::{Placement, SceneWriter},
+    utils::{functions::degrees_to_radians, interval::Interval},
     vector::{Point, Vector},
 };
 
@@ -13,9 +14,7 @@ use crate::{
 #[derive(Clone)]
 pub struct Rotation {
     hittable: Arc<dyn Hittable>,
-    x_rotation: f64,
     y_rotation: f64,
-    z_rotation: f64,
     bounding_box: Aabb,
 }
 
@@ -32,41 +31,56 @@ impl Rotation {
 
         Rotation {
             hittable,
-            x_rotation,
             y_rotation,
-            z_rotation,
             bounding_box,
         }
     }
 }
 
-impl Hittable for Rotation {
-    fn hit(&self, ray: &Ray, interval: &Interval) -> Option<HitRecord> {
-        let radians = degrees_to_radians(self.y_rotation);
-        let sin_theta = radians.sin();
-        let cos_theta = radians.cos();
+impl Rotation {
+    fn get_sin_cos(&self) -> (f64, f64) {
+        degrees_to_radians(self.y_rotation).sin_cos()
+    }
 
-        let (x_origin, y_origin, z_origin) = ray.get_origin().get_point();
-        let (x_dir, y_dir, z_dir) = ray.get_direction().get_point();
+    fn to_object_space(&self, vector: Vector) -> Vector {
+        let (sin_theta, cos_theta) = self.get_sin_cos();
+        let (x, y, z) = vector.get_point();
 
-        let new_origin = Vector::new(
-            (x_origin * cos_theta) - (z_origin * sin_theta),
-            y_origin,
-            (x_origin * sin_theta) + (z_origin * cos_theta),
-        );
+        Vector::new(
+            (x * cos_theta) - (z * sin_theta),
+            y,
+            (x * sin_theta) + (z * cos_theta),
+        )
+    }
 
-        let new_direction = Vector::new(
-            (x_dir * cos_theta) - (z_dir * sin_theta),
-            y_dir,
-            (x_dir * sin_theta) + (z_dir * cos_theta),
-        );
+    fn to_world_space(&self, vector: Vector) -> Vector {
+        let (sin_theta, cos_theta) = self.get_sin_cos();
+        let (x, y, z) = vector.get_point();
 
-        let new_ray = Ray::new(new_origin, new_direction, Some(ray.get_time()));
+        Vector::new(
+            (x * cos_theta) + (z * sin_theta),
+            y,
+            -(x * sin_theta) + (z * cos_theta),
+        )
+    }
 
-        match self.hittable.hit(&new_ray, interval) {
-            Some(mut hit) => Some(hit.rotate(&new_ray, sin_theta, cos_theta)),
-            None => None,
-        }
+    fn to_object_ray(&self, ray: &Ray) -> Ray {
+        Ray::new(
+            self.to_object_space(ray.get_origin()),
+            self.to_object_space(ray.get_direction()),
+            Some(ray.get_time()),
+        )
+    }
+}
+
+impl Hittable for Rotation {
+    fn hit(&self, ray: &Ray, interval: &Interval) -> Option<HitRecord> {
+        let (sin_theta, cos_theta) = self.get_sin_cos();
+        let new_ray = self.to_object_ray(ray);
+
+        self.hittable
+            .hit(&new_ray, interval)
+            .map(|hit| hit.rotate(&new_ray, sin_theta, cos_theta))
     }
 
     fn get_aabb(&self) -> Aabb {
@@ -76,4 +90,39 @@ impl Hittable for Rotation {
     fn clone_box(&self) -> Arc<dyn Hittable> {
         Arc::new(self.clone())
     }
+
+    fn write_scene(&self, writer: &mut SceneWriter, placement: &Placement) {
+        let object = writer.add_named_object(self.hittable.as_ref());
+
+        writer.push_object(
+            format!("rotate object={} y={}", object, self.y_rotation),
+            placement,
+        );
+    }
+
+    fn collect_lights(&self, lights: &mut HittableList) {
+        let mut inner_lights = HittableList::new();
+        self.hittable.collect_lights(&mut inner_lights);
+
+        if inner_lights.get_num_hittables() > 0 {
+            lights.add_hittable(Arc::new(Rotation::new(
+                Arc::new(inner_lights),
+                0.0,
+                self.y_rotation,
+                0.0,
+            )));
+        }
+    }
+
+    fn pdf_value(&self, ray: &Ray) -> f64 {
+        self.hittable.pdf_value(&self.to_object_ray(ray))
+    }
+
+    fn random_direction(&self, origin: Point, time: f64) -> Vector {
+        let direction = self
+            .hittable
+            .random_direction(self.to_object_space(origin), time);
+
+        self.to_world_space(direction)
+    }
 }
@@ -2,10 +2,11 @@ use std::sync::Arc;
 
 use crate::{
     bvh::aabb::Aabb,
-    objects::hittable::{HitRecord, Hittable},
+    objects::hittable::{HitRecord, Hittable, HittableList},
     ray::Ray,
+    scene_file::writer::{Placement, SceneWriter, format_vector},
     utils::interval::Interval,
-    vector::Vector,
+    vector::{Point, Vector},
 };
 
 #[derive(Clone)]
@@ -35,10 +36,9 @@ impl Hittable for Translation {
             Some(ray.get_time()),
         );
 
-        match self.hittable.hit(&offset_ray, interval) {
-            Some(mut hit) => Some(hit.translate(&offset_ray, self.offset)),
-            None => None,
-        }
+        self.hittable
+            .hit(&offset_ray, interval)
+            .map(|hit| hit.translate(&offset_ray, self.offset))
     }
 
     fn get_aabb(&self) -> Aabb {
@@ -48,4 +48,44 @@ impl Hittable for Translation {
     fn clone_box(&self) -> Arc<dyn Hittable> {
         Arc::new(self.clone())
     }
+
+    fn write_scene(&self, writer: &mut SceneWriter, placement: &Placement) {
+        let object = writer.add_named_object(self.hittable.as_ref());
+
+        writer.push_object(
+            format!(
+                "translate object={} offset={}",
+                object,
+                format_vector(self.offset)
+            ),
+            placement,
+        );
+    }
+
+    fn collect_lights(&self, lights: &mut HittableList) {
+        let mut inner_lights = HittableList::new();
+        self.hittable.collect_lights(&mut inner_lights);
+
+        if inner_lights.get_num_hittables() > 0 {
+            lights.add_hittable(Arc::new(Translation::new(
+                Arc::new(inner_lights),
+                self.offset,
+            )));
+        }
+    }
+
+    fn pdf_value(&self, ray: &Ray) -> f64 {
+        let offset_ray: Ray = Ray::new(
+            ray.get_origin().subv(self.offset),
+            ray.get_direction(),
+            Some(ray.get_time()),
+        );
+
+        self.hittable.pdf_value(&offset_ray)
+    }
+
+    fn random_direction(&self, origin: Point, time: f64) -> Vector {
+        self.hittable
+            .random_direction(origin.subv(self.offset), time)
+    }
 }
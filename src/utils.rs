@@ -1,3 +1,4 @@
 pub mod constants;
 pub mod functions;
 pub mod interval;
+pub mod onb;
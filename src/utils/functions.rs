@@ -1,21 +1,55 @@
-use rand::{Rng, thread_rng};
+use std::cell::RefCell;
+
+use rand::{Rng, SeedableRng, rngs::SmallRng, seq::SliceRandom};
 
 use crate::utils::constants::PI;
 
+// NOTE: Every random number comes from this generator, one per thread. It starts off
+// seeded from the OS, seed_rng makes whatever the current thread does next repeatable.
+thread_local! {
+    static RNG: RefCell<SmallRng> = RefCell::new(SmallRng::from_rng(&mut rand::rng()));
+}
+
+pub fn seed_rng(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = SmallRng::seed_from_u64(seed));
+}
+
+// NOTE: Folds a list of values into one well mixed seed (SplitMix64 finalizer), so
+// nearby pixels and sample indices don't get similar looking seeds
+pub fn mix_seed(values: &[u64]) -> u64 {
+    let mut hash: u64 = 0x9e3779b97f4a7c15;
+
+    for &value in values {
+        hash ^= value;
+        hash = hash.wrapping_add(0x9e3779b97f4a7c15);
+        hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d049bb133111eb);
+        hash ^= hash >> 31;
+    }
+
+    hash
+}
+
 pub fn degrees_to_radians(degree: f64) -> f64 {
     (degree * PI) / 180.0
 }
 
 pub fn random_int_in_range(min: i64, max: i64) -> i64 {
-    thread_rng().gen_range(min..max)
+    RNG.with(|rng| rng.borrow_mut().random_range(min..max))
 }
 
 pub fn random_double() -> f64 {
-    let mut rng = rand::rng();
-    rng.random()
+    RNG.with(|rng| rng.borrow_mut().random())
 }
 
 pub fn random_double_in_range(min: f64, max: f64) -> f64 {
-    let mut rng = rand::rng();
-    rng.gen_range(min..max)
+    RNG.with(|rng| rng.borrow_mut().random_range(min..max))
+}
+
+pub fn random_u64() -> u64 {
+    RNG.with(|rng| rng.borrow_mut().random())
+}
+
+pub fn shuffle<T>(values: &mut [T]) {
+    RNG.with(|rng| values.shuffle(&mut *rng.borrow_mut()));
 }
@@ -88,3 +88,71 @@ impl Display for Interval {
         write!(f, "Interval from {} to {}", self.min, self.max)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains_includes_the_ends_and_surrounds_does_not() {
+        let interval = Interval::new(-1.0, 2.0);
+
+        assert!(interval.contains(-1.0) && interval.contains(2.0) && interval.contains(0.5));
+        assert!(!interval.surrounds(-1.0) && !interval.surrounds(2.0));
+        assert!(interval.surrounds(0.5));
+        assert!(!interval.contains(2.000001) && !interval.surrounds(-1.000001));
+    }
+
+    #[test]
+    fn nan_is_never_inside() {
+        assert!(!UNIVERSAL_INTERVAL.contains(f64::NAN));
+        assert!(!UNIVERSAL_INTERVAL.surrounds(f64::NAN));
+    }
+
+    #[test]
+    fn empty_and_universal_intervals() {
+        for val in [-F_INF, -1e300, 0.0, 1e300, F_INF] {
+            assert!(!EMPTY_INTERVAL.contains(val));
+            assert!(UNIVERSAL_INTERVAL.contains(val));
+        }
+
+        assert!(EMPTY_INTERVAL.get_size() < 0.0);
+        assert_eq!(UNIVERSAL_INTERVAL.get_size(), F_INF);
+    }
+
+    #[test]
+    fn clamp_keeps_values_in_range() {
+        let interval = Interval::new(0.0, 1.0);
+
+        assert_eq!(interval.clamp(-3.0), 0.0);
+        assert_eq!(interval.clamp(0.25), 0.25);
+        assert_eq!(interval.clamp(7.0), 1.0);
+    }
+
+    #[test]
+    fn expand_pads_both_ends_by_half() {
+        let expanded = Interval::new(1.0, 2.0).expand(0.5);
+
+        assert_eq!(expanded.get_min_max(), (0.75, 2.25));
+        assert_eq!(expanded.get_size(), 1.5);
+    }
+
+    #[test]
+    fn offset_moves_without_resizing() {
+        let moved = Interval::new(1.0, 2.0).offset(-3.0);
+
+        assert_eq!(moved.get_min_max(), (-2.0, -1.0));
+    }
+
+    #[test]
+    fn merge_covers_both_intervals() {
+        let merged = merge_interval(Interval::new(0.0, 1.0), Interval::new(3.0, 4.0));
+        assert_eq!(merged.get_min_max(), (0.0, 4.0));
+
+        let nested = merge_interval(Interval::new(0.0, 10.0), Interval::new(3.0, 4.0));
+        assert_eq!(nested.get_min_max(), (0.0, 10.0));
+
+        let with_empty = merge_interval(EMPTY_INTERVAL, Interval::new(3.0, 4.0));
+        assert_eq!(with_empty.get_min_max(), (3.0, 4.0));
+    }
+}
@@ -0,0 +1,49 @@
+use crate::vector::{Vector, cross_product, dot_product};
+
+// NOTE: Orthonormal basis around a direction, so directions can be sampled around
+// the z-axis and then turned to face the right way
+#[derive(Clone, Copy)]
+pub struct Onb {
+    u: Vector,
+    v: Vector,
+    w: Vector,
+}
+
+impl Onb {
+    pub fn new(direction: Vector) -> Onb {
+        let w = direction.unit();
+        let (x, _, _) = w.get_point();
+
+        // NOTE: Any axis works as long as it's not (nearly) parallel to w
+        let a = if x.abs() > 0.9 {
+            Vector::new(0.0, 1.0, 0.0)
+        } else {
+            Vector::new(1.0, 0.0, 0.0)
+        };
+
+        let v = cross_product(w, a).unit();
+        let u = cross_product(w, v);
+
+        Onb { u, v, w }
+    }
+
+    pub fn get_w(&self) -> Vector {
+        self.w
+    }
+
+    // NOTE: From coordinates in this basis to world coordinates
+    pub fn transform(&self, local: Vector) -> Vector {
+        let (x, y, z) = local.get_point();
+
+        self.u.scale(x).addv(self.v.scale(y)).addv(self.w.scale(z))
+    }
+
+    // NOTE: From world coordinates to coordinates in this basis
+    pub fn inverse_transform(&self, world: Vector) -> Vector {
+        Vector::new(
+            dot_product(world, self.u),
+            dot_product(world, self.v),
+            dot_product(world, self.w),
+        )
+    }
+}
@@ -1,6 +1,9 @@
 use core::fmt::{Display, Formatter, Result};
 
-use crate::utils::functions::random_double_in_range;
+use crate::utils::{
+    constants::PI,
+    functions::{random_double, random_double_in_range},
+};
 
 pub type Point = Vector;
 pub type Color = Vector;
@@ -108,32 +111,19 @@ impl Vector {
         self.scale(1.0 / length)
     }
 
-    pub fn near_zero(self) -> bool {
-        let epsilon = 1e-8;
-
-        self.x.abs() < epsilon && self.y.abs() < epsilon && self.z.abs() < epsilon
+    // NOTE: Perceived brightness of a linear color (Rec. 709 weights)
+    pub fn luminance(self) -> f64 {
+        0.2126 * self.x + 0.7152 * self.y + 0.0722 * self.z
     }
 
-    pub fn to_color(self) -> [u8; 3] {
-        fn f(val: f64) -> u8 {
-            if val < 0.0 {
-                0
-            } else if val >= 1.0 {
-                255
-            } else {
-                (255.8 * val) as u8
-            }
-        }
-
-        fn linear_to_gamma(val: f64) -> f64 {
-            if val > 0.0 { val.sqrt() } else { 0.0 }
-        }
+    pub fn is_zero(self) -> bool {
+        self.x == 0.0 && self.y == 0.0 && self.z == 0.0
+    }
 
-        let r = linear_to_gamma(self.x);
-        let g = linear_to_gamma(self.y);
-        let b = linear_to_gamma(self.z);
+    pub fn near_zero(self) -> bool {
+        let epsilon = 1e-8;
 
-        [f(r), f(g), f(b)]
+        self.x.abs() < epsilon && self.y.abs() < epsilon && self.z.abs() < epsilon
     }
 }
 
@@ -183,6 +173,8 @@ pub fn get_random_unit_vector_in_range(min: f64, max: f64) -> Vector {
     )
 }
 
+// NOTE: Only the tests need directions on one side of a surface
+#[cfg(test)]
 pub fn get_random_unit_vector_on_hemisphere(normal: Vector) -> Vector {
     let unit_vector = get_random_unit_vector();
 
@@ -193,18 +185,34 @@ pub fn get_random_unit_vector_on_hemisphere(normal: Vector) -> Vector {
     }
 }
 
-pub fn get_random_vector_in_unit_disk() -> Vector {
-    loop {
-        let point = Point::new(
-            random_double_in_range(-1.0, 1.0),
-            random_double_in_range(-1.0, 1.0),
-            0.0,
-        );
+// NOTE: Cosine weighted direction around the z-axis, turn it around a normal with an Onb
+pub fn get_random_cosine_direction() -> Vector {
+    let (r1, r2) = (random_double(), random_double());
+    let phi = 2.0 * PI * r1;
 
-        if point.get_length_squared() < 1.0 {
-            break point;
-        }
+    Vector::new(
+        phi.cos() * r2.sqrt(),
+        phi.sin() * r2.sqrt(),
+        (1.0 - r2).sqrt(),
+    )
+}
+
+// NOTE: Shirley and Chiu's concentric mapping from [0, 1)^2 to the unit disk, which
+// keeps evenly spread points evenly spread (unlike rejection sampling)
+pub fn square_to_disk(u: f64, v: f64) -> (f64, f64) {
+    let (a, b) = (2.0 * u - 1.0, 2.0 * v - 1.0);
+
+    if a == 0.0 && b == 0.0 {
+        return (0.0, 0.0);
     }
+
+    let (radius, theta) = if a.abs() > b.abs() {
+        (a, (PI / 4.0) * (b / a))
+    } else {
+        (b, (PI / 2.0) - (PI / 4.0) * (a / b))
+    };
+
+    (radius * theta.cos(), radius * theta.sin())
 }
 
 pub fn reflect(u: Vector, normal: Vector) -> Vector {
@@ -231,3 +239,253 @@ impl Display for Vector {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::functions::seed_rng;
+
+    const EPSILON: f64 = 1e-9;
+
+    fn assert_close(a: Vector, b: Vector) {
+        assert!(
+            a.subv(b).get_length() < EPSILON,
+            "expected {} to be close to {}",
+            a,
+            b
+        );
+    }
+
+    fn random_vector() -> Vector {
+        Vector::new(
+            random_double_in_range(-10.0, 10.0),
+            random_double_in_range(-10.0, 10.0),
+            random_double_in_range(-10.0, 10.0),
+        )
+    }
+
+    #[test]
+    fn arithmetic_is_componentwise() {
+        let u = Vector::new(1.0, -2.0, 3.0);
+        let v = Vector::new(4.0, 5.0, -6.0);
+
+        assert_eq!(u.addv(v).get_point(), (5.0, 3.0, -3.0));
+        assert_eq!(u.subv(v).get_point(), (-3.0, -7.0, 9.0));
+        assert_eq!(u.multiply(v).get_point(), (4.0, -10.0, -18.0));
+        assert_eq!(u.scale(-2.0).get_point(), (-2.0, 4.0, -6.0));
+        assert_eq!(u.negate().get_point(), (-1.0, 2.0, -3.0));
+    }
+
+    #[test]
+    fn get_point_by_axis_picks_the_component() {
+        let u = Vector::new(1.0, 2.0, 3.0);
+
+        assert_eq!(u.get_point_by_axis(0), 1.0);
+        assert_eq!(u.get_point_by_axis(1), 2.0);
+        assert_eq!(u.get_point_by_axis(2), 3.0);
+    }
+
+    #[test]
+    fn length_of_a_3_4_12_vector() {
+        let u = Vector::new(3.0, 4.0, 12.0);
+
+        assert_eq!(u.get_length_squared(), 169.0);
+        assert_eq!(u.get_length(), 13.0);
+    }
+
+    #[test]
+    fn unit_vectors_have_length_one() {
+        seed_rng(1);
+
+        for _ in 0..1000 {
+            let u = random_vector();
+            assert!((u.unit().get_length() - 1.0).abs() < EPSILON);
+            assert!(dot_product(u.unit(), u) > 0.0);
+        }
+    }
+
+    #[test]
+    fn unit_of_the_zero_vector_is_nan() {
+        let (x, y, z) = Vector::new(0.0, 0.0, 0.0).unit().get_point();
+
+        assert!(x.is_nan() && y.is_nan() && z.is_nan());
+    }
+
+    #[test]
+    fn near_zero_only_for_tiny_components() {
+        assert!(Vector::new(1e-9, -1e-9, 0.0).near_zero());
+        assert!(!Vector::new(1e-9, 1e-7, 0.0).near_zero());
+        assert!(!Vector::new(f64::NAN, 0.0, 0.0).near_zero());
+    }
+
+    #[test]
+    fn cross_product_of_the_axes_is_right_handed() {
+        let x = Vector::new(1.0, 0.0, 0.0);
+        let y = Vector::new(0.0, 1.0, 0.0);
+        let z = Vector::new(0.0, 0.0, 1.0);
+
+        assert_close(cross_product(x, y), z);
+        assert_close(cross_product(y, z), x);
+        assert_close(cross_product(z, x), y);
+    }
+
+    #[test]
+    fn cross_product_is_perpendicular_and_anticommutative() {
+        seed_rng(2);
+
+        for _ in 0..1000 {
+            let u = random_vector();
+            let v = random_vector();
+            let n = cross_product(u, v);
+
+            assert!(dot_product(n, u).abs() < 1e-9 * n.get_length() * u.get_length() + EPSILON);
+            assert!(dot_product(n, v).abs() < 1e-9 * n.get_length() * v.get_length() + EPSILON);
+            assert_close(n, cross_product(v, u).negate());
+        }
+    }
+
+    #[test]
+    fn cross_product_of_parallel_vectors_is_zero() {
+        let u = Vector::new(1.0, 2.0, 3.0);
+
+        assert!(cross_product(u, u.scale(-2.5)).near_zero());
+    }
+
+    #[test]
+    fn dot_product_matches_lengths_and_angles() {
+        let u = Vector::new(1.0, 2.0, 3.0);
+
+        assert_eq!(dot_product(u, u), u.get_length_squared());
+        assert_eq!(
+            dot_product(Vector::new(1.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0)),
+            0.0
+        );
+        assert_eq!(dot_product(u, Vector::new(-1.0, 1.0, 2.0)), 7.0);
+    }
+
+    #[test]
+    fn luminance_of_white_is_one() {
+        assert!((Color::new(1.0, 1.0, 1.0).luminance() - 1.0).abs() < EPSILON);
+        assert_eq!(Color::new(0.0, 0.0, 0.0).luminance(), 0.0);
+    }
+
+    #[test]
+    fn random_unit_vectors_are_unit_length() {
+        seed_rng(3);
+
+        for _ in 0..1000 {
+            assert!((get_random_unit_vector().get_length() - 1.0).abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn random_hemisphere_vectors_face_the_normal() {
+        seed_rng(4);
+        let normal = Vector::new(0.3, -1.0, 0.2);
+
+        for _ in 0..1000 {
+            assert!(dot_product(get_random_unit_vector_on_hemisphere(normal), normal) > 0.0);
+        }
+    }
+
+    #[test]
+    fn square_to_disk_stays_in_the_unit_disk() {
+        assert_eq!(square_to_disk(0.5, 0.5), (0.0, 0.0));
+
+        for (u, v) in [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.5)] {
+            let (x, y) = square_to_disk(u, v);
+            assert!(((x * x + y * y).sqrt() - 1.0).abs() < EPSILON);
+        }
+
+        for i in 0..32 {
+            for j in 0..32 {
+                let (x, y) = square_to_disk(i as f64 / 31.0, j as f64 / 31.0);
+                assert!(x * x + y * y <= 1.0 + EPSILON);
+            }
+        }
+    }
+
+    #[test]
+    fn reflect_mirrors_about_the_normal() {
+        let normal = Vector::new(0.0, 1.0, 0.0);
+
+        assert_close(
+            reflect(Vector::new(1.0, -1.0, 0.0), normal),
+            Vector::new(1.0, 1.0, 0.0),
+        );
+        // NOTE: Head on comes straight back, and grazing carries on untouched
+        assert_close(
+            reflect(Vector::new(0.0, -1.0, 0.0), normal),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        assert_close(
+            reflect(Vector::new(1.0, 0.0, 0.0), normal),
+            Vector::new(1.0, 0.0, 0.0),
+        );
+    }
+
+    #[test]
+    fn reflect_keeps_length_and_undoes_itself() {
+        seed_rng(5);
+
+        for _ in 0..1000 {
+            let u = random_vector();
+            let normal = random_vector().unit();
+            let reflected = reflect(u, normal);
+
+            assert!((reflected.get_length() - u.get_length()).abs() < EPSILON);
+            assert!((dot_product(reflected, normal) + dot_product(u, normal)).abs() < EPSILON);
+            assert_close(reflect(reflected, normal), u);
+        }
+    }
+
+    #[test]
+    fn refract_with_matching_indices_goes_straight_through() {
+        seed_rng(6);
+        let normal = Vector::new(0.0, 1.0, 0.0);
+
+        for _ in 0..1000 {
+            let direction = get_random_unit_vector_on_hemisphere(normal.negate());
+            assert_close(refract(direction, normal, 1.0), direction);
+        }
+    }
+
+    #[test]
+    fn refract_head_on_is_not_bent() {
+        let normal = Vector::new(0.0, 1.0, 0.0);
+        let direction = Vector::new(0.0, -1.0, 0.0);
+
+        assert_close(refract(direction, normal, 1.0 / 1.5), direction);
+        assert_close(refract(direction, normal, 1.5), direction);
+    }
+
+    #[test]
+    fn refract_follows_snells_law() {
+        seed_rng(7);
+        let normal = Vector::new(0.0, 0.0, 1.0);
+
+        for _ in 0..1000 {
+            let direction = get_random_unit_vector_on_hemisphere(normal.negate());
+            let eta = random_double_in_range(0.5, 2.0);
+
+            let cos_in = -dot_product(direction, normal);
+            let sin_in = (1.0 - cos_in * cos_in).sqrt();
+
+            // NOTE: Past the critical angle there is nothing to refract into, callers
+            // check for that and reflect instead
+            if eta * sin_in > 1.0 {
+                continue;
+            }
+
+            let refracted = refract(direction, normal, eta);
+            let cos_out = -dot_product(refracted, normal);
+            let sin_out = (1.0 - cos_out * cos_out).max(0.0).sqrt();
+
+            assert!((refracted.get_length() - 1.0).abs() < 1e-9);
+            assert!(cos_out > 0.0, "refracted ray should carry on through");
+            assert!((sin_out - eta * sin_in).abs() < 1e-6);
+            // NOTE: Stays in the plane of incidence
+            assert!(dot_product(cross_product(direction, normal), refracted).abs() < 1e-9);
+        }
+    }
+}
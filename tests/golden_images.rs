@@ -0,0 +1,171 @@
+// NOTE: Renders every built-in scene small and with a fixed seed, and compares it
+// against a stored reference image in tests/golden. Seeded renders are bit-for-bit
+// reproducible, the tolerance is only there to absorb floating point differences
+// between platforms, so anything that fails here changed how a scene looks.
+//
+// After an intended change to how scenes look, write new references with:
+//
+//   UPDATE_GOLDEN=1 cargo test --test golden_images
+//
+// and look over the new images before committing them.
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use image::RgbImage;
+
+const BINARY: &str = env!("CARGO_BIN_EXE_raytracingInAMonth");
+
+const SEED: &str = "1";
+const WIDTH: &str = "96";
+const SAMPLES_PER_PIXEL: &str = "16";
+const MAX_DEPTH: &str = "8";
+
+// NOTE: An RMSE of about 2.5. Two renders of the same scene with different seeds are
+// 4.5 (earth) to 80 (cornell_box) apart at these settings, so even plain noise fails.
+const MIN_PSNR: f64 = 40.0;
+
+macro_rules! golden_tests {
+    ($($scene:ident),* $(,)?) => {
+        $(
+            #[test]
+            fn $scene() {
+                check_scene(stringify!($scene));
+            }
+        )*
+
+        const SCENES: &[&str] = &[$(stringify!($scene)),*];
+    };
+}
+
+golden_tests!(
+    basic,
+    camera_fov,
+    weekend,
+    checker,
+    earth,
+    perlin,
+    quad,
+    perlin_earth,
+    simple_light,
+    cornell_box,
+    cornell_smoke,
+    week,
+);
+
+#[test]
+fn every_scene_has_a_golden_test() {
+    let output = Command::new(BINARY)
+        .arg("--list")
+        .output()
+        .expect("Could not run the renderer");
+    let listed = String::from_utf8(output.stdout).expect("Scene list is not valid UTF-8");
+
+    let names: Vec<&str> = listed
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .collect();
+
+    assert_eq!(
+        names, SCENES,
+        "Scenes were added or removed, update the list in golden_tests!"
+    );
+}
+
+fn check_scene(scene: &str) {
+    let output_path =
+        PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("golden_{}.png", scene));
+    let golden_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{}.png", scene));
+
+    render_scene(scene, &output_path);
+
+    let rendered = image::open(&output_path)
+        .expect("Could not open the rendered image")
+        .to_rgb8();
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        rendered
+            .save(&golden_path)
+            .expect("Could not save the reference image");
+        return;
+    }
+
+    let golden = match image::open(&golden_path) {
+        Ok(golden) => golden.to_rgb8(),
+        Err(err) => panic!(
+            "Could not open reference image {}: {} (run with UPDATE_GOLDEN=1 to create it)",
+            golden_path.display(),
+            err
+        ),
+    };
+
+    assert_eq!(
+        rendered.dimensions(),
+        golden.dimensions(),
+        "{} rendered at a different size than its reference image",
+        scene
+    );
+
+    let rmse = get_rmse(&rendered, &golden);
+    let psnr = get_psnr(rmse);
+
+    assert!(
+        psnr >= MIN_PSNR,
+        "{} no longer matches its reference image: RMSE {:.3}, PSNR {:.2} dB (needs {} dB), \
+         see {}",
+        scene,
+        rmse,
+        psnr,
+        MIN_PSNR,
+        output_path.display()
+    );
+}
+
+fn render_scene(scene: &str, output_path: &Path) {
+    let output = Command::new(BINARY)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args([
+            scene,
+            "--seed",
+            SEED,
+            "--width",
+            WIDTH,
+            "--samples-per-pixel",
+            SAMPLES_PER_PIXEL,
+            "--max-depth",
+            MAX_DEPTH,
+            "--output",
+        ])
+        .arg(output_path)
+        .output()
+        .expect("Could not run the renderer");
+
+    assert!(
+        output.status.success(),
+        "Rendering {} failed: {}",
+        scene,
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+// NOTE: Root mean square error over every channel of every pixel, in 0-255 units
+fn get_rmse(a: &RgbImage, b: &RgbImage) -> f64 {
+    let squared_error: f64 = a
+        .as_raw()
+        .iter()
+        .zip(b.as_raw())
+        .map(|(&x, &y)| (x as f64 - y as f64).powi(2))
+        .sum();
+
+    (squared_error / a.as_raw().len() as f64).sqrt()
+}
+
+// NOTE: Peak signal to noise ratio in dB, identical images come out as infinity
+fn get_psnr(rmse: f64) -> f64 {
+    20.0 * (255.0 / rmse).log10()
+}